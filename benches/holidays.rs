@@ -0,0 +1,18 @@
+// Batch computation of holidays, e.g. an app scrolling through years.
+use criterion::{criterion_group, criterion_main, Criterion};
+use jpn_holiday_atlas::datebook::calendar::holidays;
+use std::hint::black_box;
+
+fn bench_holidays(c: &mut Criterion) {
+    c.bench_function("holidays 2024", |b| b.iter(|| holidays(black_box(2024)).unwrap()));
+    c.bench_function("holidays 1950-2099", |b| {
+        b.iter(|| {
+            for year in 1950..=2099 {
+                black_box(holidays(black_box(year)).unwrap());
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_holidays);
+criterion_main!(benches);
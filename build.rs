@@ -0,0 +1,314 @@
+// Generates the equinox table compiled into the crate.
+//
+// Days are computed with the approximation formula published by the National Astronomical
+// Observatory of Japan, then the entries in `src/resources/equinox_base_dates.csv` are applied
+// as manual overrides. The covered range defaults to 1900-2099 and can be changed with
+// `JPN_HOLIDAY_ATLAS_EQUINOX_RANGE=START-END` (within 1900-2150, the span the formula is defined for).
+//
+// The holiday data embedded by `timebase` is validated as well, so that broken data fails the build
+// rather than the first lookup at runtime:
+// - `base.csv`: the header, a non-empty name, `relative` being true or false, a `M/D` date existing in
+//   a leap year for fixed holidays, a `month:n:weekday` condition with n in 1-5 for relative ones, and
+//   `since` not after `until`. No name may be in force twice in the same year.
+// - `special_holidays.csv`: the header, a `M/D` date existing in the year, and a name of `base.csv`
+//   in force that year, relocated at most once.
+// - `one_off_holidays.csv`: the header, a `M/D` date existing in the year, a name not in `base.csv`
+//   and a law reference, each holiday listed once.
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const RANGE_ENV: &str = "JPN_HOLIDAY_ATLAS_EQUINOX_RANGE";
+const OVERRIDES: &str = "src/resources/equinox_base_dates.csv";
+const BASE: &str = "src/resources/base.csv";
+const SPECIAL: &str = "src/resources/special_holidays.csv";
+const ONE_OFF: &str = "src/resources/one_off_holidays.csv";
+const BASE_HEADER: &str = "name,date,relative,condition,law_reference,since,until";
+const SPECIAL_HEADER: &str = "year,name,date";
+const ONE_OFF_HEADER: &str = "year,name,date,law_reference";
+const DEFAULT_RANGE: (u32, u32) = (1900, 2099);
+const FORMULA_RANGE: (u32, u32) = (1900, 2150);
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed={}", OVERRIDES);
+    println!("cargo:rerun-if-changed={}", BASE);
+    println!("cargo:rerun-if-changed={}", SPECIAL);
+    println!("cargo:rerun-if-changed={}", ONE_OFF);
+    println!("cargo:rerun-if-env-changed={}", RANGE_ENV);
+
+    let rules = validate_base(&read(BASE)).unwrap_or_else(|e| panic!("{}:{}", BASE, e));
+    validate_special(&read(SPECIAL), &rules).unwrap_or_else(|e| panic!("{}:{}", SPECIAL, e));
+    validate_one_off(&read(ONE_OFF), &rules).unwrap_or_else(|e| panic!("{}:{}", ONE_OFF, e));
+
+    let (start, end) = match env::var(RANGE_ENV) {
+        Ok(value) => parse_range(&value).unwrap_or_else(|e| panic!("{}: {}", RANGE_ENV, e)),
+        Err(_) => DEFAULT_RANGE,
+    };
+
+    let formula: Vec<(u32, u32, u32)> = (start..=end)
+        .map(|year| {
+            let (spring, fall) = formula_days(year);
+            (year, spring, fall)
+        })
+        .collect();
+
+    let mut table = formula.clone();
+    let overrides = fs::read_to_string(OVERRIDES).unwrap_or_else(|e| panic!("{}: {}", OVERRIDES, e));
+    for (line_no, line) in overrides.lines().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = parse_override(line).unwrap_or_else(|e| panic!("{}:{}: {}", OVERRIDES, line_no + 1, e));
+        match table.iter_mut().find(|x| x.0 == entry.0) {
+            Some(x) => *x = entry,
+            None => table.push(entry),
+        }
+    }
+    table.sort();
+
+    let mut out = String::new();
+    out.push_str("// Generated by build.rs. Do not edit.\n");
+    out.push_str(&format!("pub const EQUINOX_RANGE: (u32, u32) = ({}, {});\n", start, end));
+    out.push_str("// (year, day in March, day in September) computed by the formula\n");
+    out.push_str(&format!("pub static EQUINOX_FORMULA: &[(u32, u32, u32)] = &{:?};\n", formula));
+    out.push_str("// EQUINOX_FORMULA with the manual overrides applied\n");
+    out.push_str(&format!("pub static EQUINOX_TABLE: &[(u32, u32, u32)] = &{:?};\n", table));
+
+    let path = Path::new(&env::var("OUT_DIR").unwrap()).join("equinox_table.rs");
+    fs::write(path, out).unwrap();
+}
+
+fn parse_range(value: &str) -> Result<(u32, u32), String> {
+    let (start, end) = value.split_once('-').ok_or("expected START-END")?;
+    let start: u32 = start.trim().parse().map_err(|_| format!("invalid start year {:?}", start))?;
+    let end: u32 = end.trim().parse().map_err(|_| format!("invalid end year {:?}", end))?;
+    if start > end || start < FORMULA_RANGE.0 || end > FORMULA_RANGE.1 {
+        return Err(format!("range must be within {}-{}", FORMULA_RANGE.0, FORMULA_RANGE.1));
+    }
+    Ok((start, end))
+}
+
+// Day of March and September of the equinoxes in `year`.
+fn formula_days(year: u32) -> (u32, u32) {
+    let y = year as i64;
+    let (spring, fall, leap) = if y < 1980 {
+        (20.8357, 23.2588, (y - 1983) / 4)
+    } else if y < 2100 {
+        (20.8431, 23.2488, (y - 1980) / 4)
+    } else {
+        (21.8510, 24.2488, (y - 1980) / 4)
+    };
+    let drift = 0.242194 * (y - 1980) as f64 - leap as f64;
+    ((spring + drift) as u32, (fall + drift) as u32)
+}
+
+// `2024,3/20,9/22`
+fn parse_override(line: &str) -> Result<(u32, u32, u32), String> {
+    let m: Vec<&str> = line.split(',').map(str::trim).collect();
+    if m.len() != 3 {
+        return Err(format!("expected year,spring,fall but got {:?}", line));
+    }
+    let year: u32 = m[0].parse().map_err(|_| format!("invalid year {:?}", m[0]))?;
+    // The equinoxes never move outside March 19-22 and September 21-24
+    let day = |value: &str, month: u32, days: std::ops::RangeInclusive<u32>| -> Result<u32, String> {
+        let dd: u32 = match value.split_once('/') {
+            Some((mm, dd)) if mm.parse() == Ok(month) => {
+                dd.parse().map_err(|_| format!("invalid day in {:?} for {}", value, year))?
+            }
+            _ => return Err(format!("expected {}/DD but got {:?} for {}", month, value, year)),
+        };
+        if !days.contains(&dd) {
+            return Err(format!("{:?} for {} is not a plausible equinox date", value, year));
+        }
+        Ok(dd)
+    };
+    Ok((year, day(m[1], 3, 19..=22)?, day(m[2], 9, 21..=24)?))
+}
+
+fn read(path: &str) -> String {
+    fs::read_to_string(path).unwrap_or_else(|e| panic!("{}: {}", path, e))
+}
+
+// A rule of `base.csv`: name, since and until
+type Period = (String, Option<u32>, Option<u32>);
+
+// Errors are prefixed with the line number.
+fn validate_base(text: &str) -> Result<Vec<Period>, String> {
+    let mut lines = text.lines().enumerate();
+    match lines.next() {
+        Some((_, header)) if header.trim() == BASE_HEADER => {}
+        _ => return Err(format!("1: expected the header {}", BASE_HEADER)),
+    }
+    let mut rules: Vec<Period> = Vec::new();
+    for (line_no, line) in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let rule = parse_base(line).map_err(|e| format!("{}: {}", line_no + 1, e))?;
+        let overlap = rules.iter().find(|(name, since, until)| {
+            *name == rule.0 && since.max(&rule.1).unwrap_or(0) <= until.unwrap_or(u32::MAX).min(rule.2.unwrap_or(u32::MAX))
+        });
+        if let Some((_, since, until)) = overlap {
+            return Err(format!(
+                "{}: {} overlaps its rule in force {}-{}",
+                line_no + 1, rule.0, since.map_or(String::new(), |y| y.to_string()), until.map_or(String::new(), |y| y.to_string())
+            ));
+        }
+        rules.push(rule);
+    }
+    Ok(rules)
+}
+
+// `成人の日,,true,january:2:monday,国民の祝日に関する法律 第2条,2000,`
+fn parse_base(line: &str) -> Result<Period, String> {
+    let m: Vec<&str> = line.split(',').map(str::trim).collect();
+    if m.len() != BASE_HEADER.split(',').count() {
+        return Err(format!("expected {} but got {:?}", BASE_HEADER, line));
+    }
+    let name = m[0];
+    if name.is_empty() {
+        return Err("the name is empty".to_string());
+    }
+    match (m[2], m[1], m[3]) {
+        ("true", "", condition) => parse_condition(condition).map_err(|e| format!("{}: {}", name, e))?,
+        ("true", date, _) => return Err(format!("{} is relative but has the date {:?}", name, date)),
+        ("false", date, "") => {
+            // 2/29 exists in a leap year
+            parse_month_day(date, true).map_err(|e| format!("{}: {}", name, e))?;
+        }
+        ("false", _, condition) => return Err(format!("{} is fixed but has the condition {:?}", name, condition)),
+        (relative, _, _) => return Err(format!("relative must be true or false for {} but got {:?}", name, relative)),
+    }
+    let year = |value: &str| -> Result<Option<u32>, String> {
+        match value {
+            "" => Ok(None),
+            v => v.parse().map(Some).map_err(|_| format!("invalid year {:?} for {}", v, name)),
+        }
+    };
+    let (since, until) = (year(m[5])?, year(m[6])?);
+    if let (Some(since), Some(until)) = (since, until) {
+        if since > until {
+            return Err(format!("{} is in force since {} but until {}", name, since, until));
+        }
+    }
+    Ok((name.to_string(), since, until))
+}
+
+// `january:2:monday`, with the spellings `calendar` accepts
+fn parse_condition(condition: &str) -> Result<(), String> {
+    let c: Vec<&str> = condition.split(':').collect();
+    if c.len() != 3 {
+        return Err(format!("expected month:n:weekday but got {:?}", condition));
+    }
+    const MONTHS: [&str; 23] = [
+        "january", "jan", "february", "feb", "march", "mar", "april", "apr", "may", "june", "jun",
+        "july", "jul", "august", "aug", "september", "sep", "october", "oct", "november", "nov", "december", "dec",
+    ];
+    const WEEKDAYS: [&str; 14] = [
+        "monday", "mon", "tuesday", "tue", "wednesday", "wed", "thursday", "thu", "friday", "fri", "saturday", "sat", "sunday", "sun",
+    ];
+    if !MONTHS.contains(&c[0].trim().to_lowercase().as_str()) {
+        return Err(format!("unknown month {:?}", c[0]));
+    }
+    if !matches!(c[1].parse::<u32>(), Ok(1..=5)) {
+        return Err(format!("n must be 1-5 but got {:?}", c[1]));
+    }
+    if !WEEKDAYS.contains(&c[2].trim().to_lowercase().as_str()) {
+        return Err(format!("unknown weekday {:?}", c[2]));
+    }
+    Ok(())
+}
+
+// `M/D`, leading zeros accepted
+fn parse_month_day(date: &str, leap: bool) -> Result<(u32, u32), String> {
+    let (month, day) = date.split_once('/').ok_or_else(|| format!("expected M/D but got {:?}", date))?;
+    let month: u32 = month.trim().parse().map_err(|_| format!("invalid month in {:?}", date))?;
+    let day: u32 = day.trim().parse().map_err(|_| format!("invalid day in {:?}", date))?;
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return Err(format!("{:?} has no month {}", date, month)),
+    };
+    if !(1..=days).contains(&day) {
+        return Err(format!("{:?} does not exist", date));
+    }
+    Ok((month, day))
+}
+
+// Errors are prefixed with the line number.
+fn validate_special(text: &str, rules: &[Period]) -> Result<(), String> {
+    let mut lines = text.lines().enumerate();
+    match lines.next() {
+        Some((_, header)) if header.trim() == SPECIAL_HEADER => {}
+        _ => return Err(format!("1: expected the header {}", SPECIAL_HEADER)),
+    }
+    let mut seen: Vec<(u32, String)> = Vec::new();
+    for (line_no, line) in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = parse_special(line, rules).map_err(|e| format!("{}: {}", line_no + 1, e))?;
+        if seen.contains(&entry) {
+            return Err(format!("{}: {} is relocated twice in {}", line_no + 1, entry.1, entry.0));
+        }
+        seen.push(entry);
+    }
+    Ok(())
+}
+
+// `2021,海の日,7/22`
+fn parse_special(line: &str, rules: &[Period]) -> Result<(u32, String), String> {
+    let m: Vec<&str> = line.split(',').map(str::trim).collect();
+    if m.len() != 3 {
+        return Err(format!("expected {} but got {:?}", SPECIAL_HEADER, line));
+    }
+    let year: u32 = m[0].parse().map_err(|_| format!("invalid year {:?}", m[0]))?;
+    let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+    parse_month_day(m[2], leap).map_err(|e| format!("{} in {}: {}", m[1], year, e))?;
+    let in_force = rules.iter().any(|(name, since, until)| {
+        name == m[1] && since.is_none_or(|since| since <= year) && until.is_none_or(|until| year <= until)
+    });
+    if !in_force {
+        return Err(format!("{} is not a holiday of base.csv in {}", m[1], year));
+    }
+    Ok((year, m[1].to_string()))
+}
+
+// Errors are prefixed with the line number.
+fn validate_one_off(text: &str, rules: &[Period]) -> Result<(), String> {
+    let mut lines = text.lines().enumerate();
+    match lines.next() {
+        Some((_, header)) if header.trim() == ONE_OFF_HEADER => {}
+        _ => return Err(format!("1: expected the header {}", ONE_OFF_HEADER)),
+    }
+    let mut seen: Vec<(u32, String)> = Vec::new();
+    for (line_no, line) in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = parse_one_off(line, rules).map_err(|e| format!("{}: {}", line_no + 1, e))?;
+        if seen.contains(&entry) {
+            return Err(format!("{}: {} is listed twice in {}", line_no + 1, entry.1, entry.0));
+        }
+        seen.push(entry);
+    }
+    Ok(())
+}
+
+// `2019,即位の日,5/1,天皇の即位の日及び即位礼正殿の儀の行われる日を休日とする法律`
+fn parse_one_off(line: &str, rules: &[Period]) -> Result<(u32, String), String> {
+    let m: Vec<&str> = line.split(',').map(str::trim).collect();
+    if m.len() != 4 || m[1].is_empty() || m[3].is_empty() {
+        return Err(format!("expected {} but got {:?}", ONE_OFF_HEADER, line));
+    }
+    let year: u32 = m[0].parse().map_err(|_| format!("invalid year {:?}", m[0]))?;
+    let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+    parse_month_day(m[2], leap).map_err(|e| format!("{} in {}: {}", m[1], year, e))?;
+    if rules.iter().any(|(name, _, _)| name == m[1]) {
+        return Err(format!("{} is a holiday of base.csv, relocate it in special_holidays.csv instead", m[1]));
+    }
+    Ok((year, m[1].to_string()))
+}
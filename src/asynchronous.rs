@@ -0,0 +1,43 @@
+//! # Async
+//! Holiday lookups for async servers, enabled with the `tokio` feature.
+//!
+//! The computation is CPU-bound, so calling it directly from a handler blocks the executor thread.
+//! `holidays_async` runs it on tokio's blocking thread pool with `spawn_blocking` instead, and
+//! must be awaited inside a tokio runtime.
+//!
+//! ## axum
+//! ```ignore
+//! use axum::{extract::Path, http::StatusCode, routing::get, Router};
+//! use jpn_holiday_atlas::asynchronous::holidays_async;
+//!
+//! async fn holidays_handler(Path(year): Path<i32>) -> Result<String, StatusCode> {
+//!     holidays_async(year).await.map_err(|_| StatusCode::BAD_REQUEST)
+//! }
+//!
+//! let app: Router = Router::new().route("/holidays/{year}", get(holidays_handler));
+//! ```
+use anyhow::{anyhow, Result};
+use crate::datebook::calendar::{holiday, OutputFormat};
+use crate::datebook::year::Year;
+
+/// The holidays of `year` as JSON, computed on the blocking thread pool.
+pub async fn holidays_async(year: impl Into<Year>) -> Result<String> {
+    let year = year.into();
+    tokio::task::spawn_blocking(move || holiday(OutputFormat::JSON, year))
+        .await
+        .map_err(|e| anyhow!("holiday computation for {} did not complete: {}", year, e))?
+}
+
+#[cfg(test)]
+pub mod test {
+    use pretty_assertions::assert_eq;
+    use crate::datebook::calendar::{holiday, OutputFormat};
+
+    #[test]
+    pub fn test_holidays_async() {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let json = runtime.block_on(super::holidays_async(2024)).unwrap();
+        assert_eq!(json, holiday(OutputFormat::JSON, 2024).unwrap());
+        assert!(runtime.block_on(super::holidays_async(-1)).is_err());
+    }
+}
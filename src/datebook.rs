@@ -1,2 +1,14 @@
+// Forwards to `tracing::debug!` when the `tracing` feature is enabled, and expands to nothing otherwise.
+macro_rules! trace_step {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+
 pub mod timebase;
 pub mod calendar;
+pub mod rules;
+pub mod error;
+pub mod year;
+pub mod ical;
@@ -2,19 +2,31 @@
 //! This module is for calendar.
 //! ## Example
 //! ```
-//! use datebook::calendar::{holiday, OutputFormat};
+//! use jpn_holiday_atlas::datebook::calendar::{holiday, OutputFormat};
 //! let year = 2024;
 //! let format = OutputFormat::YAML;
 //! let result = holiday(format, year).unwrap();
 //! println!("{}", result);
 //! ```
 //!
+//! Optional fields are enabled through [`OutputBuilder`]:
+//! ```
+//! use jpn_holiday_atlas::datebook::calendar::{OutputBuilder, OutputFormat};
+//! let result = OutputBuilder::new(OutputFormat::JSON)
+//!     .with_rfc3339(true)
+//!     .build(2024)
+//!     .unwrap();
+//! println!("{}", result);
+//! ```
+//!
 //! ## Output Format
 //! | Format | Description |
 //! | --- | --- |
 //! | JSON | JSON format |
 //! | YAML | YAML format |
 //! | CSV | CSV format |
+//! | NDJSON | One JSON object per line, each line terminated by a newline |
+//! | TOML | A `[[holidays]]` array of tables (`toml` feature) |
 //!
 //! ## Output Example
 //! ### JSON
@@ -38,225 +50,3763 @@
 //! name,date,substitute
 //! 元旦,2024-01-01,false
 //! ```
+//! ## Optional Fields
+//! | Field | Builder | Description |
+//! | --- | --- | --- |
+//! | month, day | `Fields::MONTH`, `Fields::DAY`, with_month_day | Month and day of the date as integers |
+//! | datetime_rfc3339 | `Fields::RFC3339`, with_rfc3339 | Midnight in JST as RFC3339, e.g. `2024-01-01T00:00:00+09:00` |
+//! | category | `Fields::CATEGORY` | `fixed`, `happy_monday`, `equinox`, `substitute`, `national` or `special` |
+//! | law_reference | `Fields::LAW_REFERENCE` | The article establishing the holiday, e.g. `国民の祝日に関する法律 第2条` |
+//! | confidence | `Fields::CONFIDENCE` | `enacted`, `announced` or `predicted`, e.g. formula-based equinoxes |
+//! | long_weekend_length | `Fields::LONG_WEEKEND` | Days of the 3連休 or longer break the holiday is part of, weekends included; absent otherwise |
+//! | cluster_index | `Fields::CLUSTER_INDEX` | `[position, length]` of the holiday in its break of 2 or more days off, e.g. `[2, 4]`, for "day 2 of 4" labels; `2/4` in CSV |
+//! | name_en | `Fields::NAME_EN`, with_bilingual | The English name, e.g. `New Year's Day`, see [`english_name`] |
+//! | era | `Fields::ERA`, with_era | The Japanese era year of the date, e.g. `令和6年`; 2019 is `平成31年` until April 30, see [`era_name`] |
+//! | time | `Fields::TIME`, with_time | Midnight in JST as Unix time, e.g. `1704034800`; in milliseconds with `time_unit(TimeUnit::Millis)` |
+//!
+//! `with_bom` prepends the UTF-8 BOM to the CSV output, for opening it in Excel.
+//!
+//! `sort` takes [`SortOrder::Descending`] to list the most recent holidays first.
+//!
+//! `fields` selects exactly which of these are output, e.g. `Fields::NONE` for only `name`, `date` and `substitute`.
+//!
+//! Two holidays on the same date, only possible with custom holidays or [`holidays_with_overrides`], are
+//! listed once with both names, e.g. `海の日・山の日`.
+//!
+//! Substitute holidays are named `振替休日(こどもの日)` by default; `substitute_naming` takes a
+//! [`SubstituteNaming`] to use the bare `振替休日` or a custom label instead.
+//!
+//! ## Calendars
+//! The free functions compute the national holidays through [`JapanNationalCalendar`].
+//! Other holiday sources, such as a company calendar, can implement [`Calendar`] as well,
+//! and [`CombinedCalendar`] unions several of them.
+//!
 //! ## Note
 //! This module outputs a list of Japanese holidays based on the National Holidays Law.
-//! Variations due to special events cannot be handled.
+//! Holidays moved for special events in one year only, such as 海の日, スポーツの日 and 山の日 for the
+//! Tokyo Olympics in 2020 and 2021, are listed in `special_holidays.csv` and applied as relocations.
+//! Holidays held once by their own law, such as 昭和天皇の大喪の礼 in 1989 and 即位の日 in 2019, are listed in
+//! `one_off_holidays.csv` with the category `special`; like any holiday they can cause a 振替休日 or 国民の休日.
+//!
+//! Each year follows the law in force that year, back to 1948: the `since` and `until` columns of `base.csv`
+//! bound every rule, e.g. 天皇誕生日 moved from April 29 to December 23 in 1989 and to February 23 in 2020.
+//! [`holidays_as_of`] applies the law of another year instead.
+//! A holiday can be missing for a year between two rules, like 天皇誕生日 in 2019; [`year_notes`] explains such gaps.
 //!
 //! Note: The exact dates of future vernal equinoxes and autumnal equinoxes cannot be calculated.
 //! This is due to the need for astronomical data. However,
-//! we use the predictions of Japanese observatories up to the year 2050.
+//! we use the predictions of Japanese observatories up to the year 2050,
+//! and the approximation formula for the other years of the build-time equinox table.
 //! https://www8.cao.go.jp/chosei/shukujitsu/gaiyou.html
 //!
+//! `timebase::data_hash` identifies the embedded data, e.g. to invalidate cached output when a new crate
+//! version changes it; [`holidays_value`] includes it as `data_hash`.
+//!
+//! Only the equinoxes up to `EQUINOX_OFFICIAL_UNTIL` (in `timebase`) are officially announced and carry
+//! [`Confidence::Enacted`]; every later equinox, and any holiday derived from one, is [`Confidence::Predicted`].
+//! Raise the constant as new announcements are published.
+//!
+//! `equinox_source` on the builder, or [`JapanNationalCalendar::equinox_source`], takes an
+//! `EquinoxSource` (in `timebase`) to force one strategy: `OfficialTable` omits the equinoxes after
+//! `EQUINOX_OFFICIAL_UNTIL`, `Formula` ignores the projected dates, and `TableThenFormula` is the default.
+//!
 #[allow(unused_imports)]
 use std::fs;
-use chrono::{Datelike, Duration, Weekday, NaiveDate, Local, DateTime};
-use chrono::TimeZone;
-use anyhow::{Result, Error};
+use std::fmt;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::hash_map::Entry;
+use chrono::{Datelike, Duration, Weekday, NaiveDate, NaiveTime, DateTime, FixedOffset, TimeZone, Utc};
+use anyhow::{anyhow, Result, Error};
 use serde::Serialize;
-use serde_json::to_string_pretty;
-use super::timebase::{get_schedule, get_equinox_dates, Condition};
+use std::io::Write;
+use std::ops::{BitOr, BitOrAssign, Range};
+use super::timebase::{one_off_schedule, OneOffHoliday, EquinoxSource};
+use super::timebase::{schedule, special_schedule, schedule_names, parse_custom_holidays, data_hash, BaseHolyday, SpecialHoliday};
+use super::timebase::{VERNAL_EQUINOX_NAME, AUTUMNAL_EQUINOX_NAME, EQUINOX_LAW_REFERENCE, EQUINOX_OFFICIAL_UNTIL};
+use super::timebase::{equinox_days, VERNAL_EQUINOX_SINCE, AUTUMNAL_EQUINOX_SINCE};
+use super::rules::{rules_as_of, rules_with, Rule, SubstituteNaming, SUBSTITUTE_HOLIDAY_NAME, NATIONAL_HOLIDAY_NAME};
+use super::rules::{NATIONAL_HOLIDAY_SINCE, SUBSTITUTE_SINCE, SUBSTITUTE_SINCE_DATE, SUBSTITUTE_ROLLING_SINCE, SubstituteMode, substitute_rule_for_year};
+use super::rules::{SUBSTITUTE_HOLIDAY_LAW_REFERENCE, NATIONAL_HOLIDAY_LAW_REFERENCE, MERGED_NAME_SEPARATOR};
+use super::error::HolidayError;
+use super::year::Year;
 
-#[derive(Debug)]
-#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum OutputFormat {
     JSON,
     CSV,
     YAML,
+    NDJSON,
+    /// A `[[holidays]]` array of tables, with the `toml` feature
+    #[cfg(feature = "toml")]
+    TOML,
 }
-#[derive(Serialize)]
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub struct Holiday {
     pub name: String,
     pub date: NaiveDate,
     pub substitute: bool,
+    pub category: HolidayCategory,
+    /// The holiday a substitute holiday stands in for
+    pub source: Option<String>,
+    pub confidence: Confidence,
 }
 
-pub fn holiday(format:OutputFormat, year: u32)-> Result<String, Error> {
-    //List of holidays stipulated in the Holidays Act
-    let mut m = prepara(year);
-    let e= pick_exuinox_from_year(year);
-    m.extend(e);
-    substitute_adjustment(&mut m);
+// By date, then statutory holidays before substitutes, then by name.
+// Category and source only break the remaining ties to stay consistent with `Eq`.
+impl Ord for Holiday {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.date.cmp(&other.date)
+            .then(self.substitute.cmp(&other.substitute))
+            .then_with(|| self.name.cmp(&other.name))
+            .then(self.category.cmp(&other.category))
+            .then_with(|| self.source.cmp(&other.source))
+            .then(self.confidence.cmp(&other.confidence))
+    }
+}
+
+impl PartialOrd for Holiday {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Merges two holiday lists that are each already sorted, keeping the result sorted.
+pub fn merge_sorted(a: Vec<Holiday>, b: Vec<Holiday>) -> Vec<Holiday> {
+    let mut merged = Vec::with_capacity(a.len() + b.len());
+    let mut a = a.into_iter().peekable();
+    let mut b = b.into_iter().peekable();
+    while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+        let next = if x <= y { a.next() } else { b.next() };
+        merged.extend(next);
+    }
+    merged.extend(a);
+    merged.extend(b);
+    merged
+}
+
+/// How certain a holiday's date is.
+/// Ordered from most to least certain, so the maximum of several is the weakest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Confidence {
+    /// Established by law or officially announced equinox dates
+    Enacted,
+    /// Announced by the government but not yet enacted
+    Announced,
+    /// Computed ahead of any announcement, e.g. equinoxes from the approximation formula
+    Predicted,
+}
+
+impl Confidence {
+    /// Equinox days are official up to `EQUINOX_OFFICIAL_UNTIL` and predicted after
+    pub fn for_equinox(year: u32) -> Confidence {
+        if year <= EQUINOX_OFFICIAL_UNTIL {
+            Confidence::Enacted
+        } else {
+            Confidence::Predicted
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Confidence::Enacted => "enacted",
+            Confidence::Announced => "announced",
+            Confidence::Predicted => "predicted",
+        }
+    }
+}
+
+/// Where a holiday's date comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HolidayCategory {
+    /// Same month and day every year, e.g. 元旦
+    Fixed,
+    /// Nth weekday of a month (ハッピーマンデー), e.g. 成人の日
+    HappyMonday,
+    /// 春分の日 and 秋分の日
+    Equinox,
+    /// 振替休日
+    Substitute,
+    /// 国民の休日
+    National,
+    /// Held in one year only by its own law, e.g. 即位の日 in 2019
+    Special,
+}
+
+impl HolidayCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HolidayCategory::Fixed => "fixed",
+            HolidayCategory::HappyMonday => "happy_monday",
+            HolidayCategory::Equinox => "equinox",
+            HolidayCategory::Substitute => "substitute",
+            HolidayCategory::National => "national",
+            HolidayCategory::Special => "special",
+        }
+    }
 
-    //sort
-    m.sort_by(|a, b| a.date.cmp(&b.date));
+    /// Whether the date of holidays in this category shifts from year to year by rule.
+    pub fn is_movable(&self) -> bool {
+        matches!(self, HolidayCategory::HappyMonday | HolidayCategory::Equinox)
+    }
+}
+
+/// How the date of a holiday is determined each year, see `holiday_rule`.
+/// Displays in Japanese, e.g. `1月第2月曜日`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HolidayRule {
+    /// Same month and day every year
+    Fixed { month: u32, day: u32 },
+    /// The nth `weekday` of `month`
+    NthWeekday { month: u32, n: u32, weekday: Weekday },
+    /// The equinox day of `month`, 3 or 9
+    Equinox { month: u32 },
+}
 
-    match format {
-        OutputFormat::CSV => {
-            let mut csv = String::new();
-            csv.push_str("name,date,substitute\n");
-            for d in m {
-                csv.push_str(&format!("{},{},{}\n", d.name, d.date, d.substitute));
+impl fmt::Display for HolidayRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HolidayRule::Fixed { month, day } => write!(f, "{}月{}日", month, day),
+            HolidayRule::NthWeekday { month, n, weekday } => {
+                let name = ["月", "火", "水", "木", "金", "土", "日"][weekday.num_days_from_monday() as usize];
+                write!(f, "{}月第{}{}曜日", month, n, name)
             }
-            Ok(csv)
-        },
-        OutputFormat::JSON => {
-            let json = to_string_pretty(&m).unwrap();
-            Ok(json)
-        },
-        OutputFormat::YAML => {
-            let yaml = serde_yaml::to_string(&m).unwrap();
-            Ok(yaml)
+            HolidayRule::Equinox { month: 3 } => write!(f, "春分日"),
+            HolidayRule::Equinox { .. } => write!(f, "秋分日"),
         }
     }
+}
 
+/// A change to one holiday for a single computation, see `holidays_with_overrides`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleOverride {
+    /// Places the holiday `name` by `rule` instead of its statutory rule
+    Rule { name: String, rule: HolidayRule },
+    /// Removes the holiday `name`
+    Disable { name: String },
 }
 
-// private functions
+/// A holiday as it appears in the formatted output.
+/// Keys are snake_case; `test_output_keys` pins the exact set.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct HolidayShapedItem {
+    pub name: String,
+    pub date: NaiveDate,
+    pub substitute: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub month: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub datetime_rfc3339: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<HolidayCategory>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub law_reference: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<Confidence>,
+    /// Days in the run of 3 or more days off including the holiday, counting weekends, e.g. 3 for a 3連休
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub long_weekend_length: Option<u32>,
+    /// Position of the holiday in its run of 2 or more days off and the run's length, e.g. (2, 3) for the
+    /// middle day of a 3連休. Output as `[2, 3]`, and as `2/3` in CSV
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cluster_index: Option<(u32, u32)>,
+    /// The English name, e.g. `Substitute Holiday (Children's Day)` for `振替休日(こどもの日)`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_en: Option<String>,
+    /// The Japanese era year of the date, e.g. `令和6年`, see `era_name`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub era: Option<String>,
+    /// Midnight in JST as Unix time, in the builder's `TimeUnit`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time: Option<i64>,
+}
+
+impl HolidayShapedItem {
+    // Values of the optional fields in output order, `None` where not selected
+    fn optional_values(&self) -> [Option<String>; 11] {
+        [
+            self.month.map(|v| v.to_string()),
+            self.day.map(|v| v.to_string()),
+            self.datetime_rfc3339.clone(),
+            self.category.map(|v| v.as_str().to_string()),
+            self.law_reference.clone(),
+            self.confidence.map(|v| v.as_str().to_string()),
+            self.long_weekend_length.map(|v| v.to_string()),
+            self.cluster_index.map(|(position, total)| format!("{}/{}", position, total)),
+            self.name_en.clone(),
+            self.era.clone(),
+            self.time.map(|v| v.to_string()),
+        ]
+    }
+}
+
+/// Selects the optional fields of `HolidayShapedItem` to include in the output.
+/// `name`, `date` and `substitute` are always present.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fields(u32);
+
+impl Fields {
+    pub const NONE: Fields = Fields(0);
+    pub const MONTH: Fields = Fields(1);
+    pub const DAY: Fields = Fields(1 << 1);
+    pub const RFC3339: Fields = Fields(1 << 2);
+    pub const CATEGORY: Fields = Fields(1 << 3);
+    pub const LAW_REFERENCE: Fields = Fields(1 << 4);
+    pub const CONFIDENCE: Fields = Fields(1 << 5);
+    pub const LONG_WEEKEND: Fields = Fields(1 << 6);
+    pub const CLUSTER_INDEX: Fields = Fields(1 << 7);
+    pub const NAME_EN: Fields = Fields(1 << 8);
+    pub const ERA: Fields = Fields(1 << 9);
+    pub const TIME: Fields = Fields(1 << 10);
+    pub const ALL: Fields = Fields((1 << 11) - 1);
+
+    // Field names in output order, matching `HolidayShapedItem::optional_values`
+    const NAMES: [(Fields, &'static str); 11] = [
+        (Fields::MONTH, "month"),
+        (Fields::DAY, "day"),
+        (Fields::RFC3339, "datetime_rfc3339"),
+        (Fields::CATEGORY, "category"),
+        (Fields::LAW_REFERENCE, "law_reference"),
+        (Fields::CONFIDENCE, "confidence"),
+        (Fields::LONG_WEEKEND, "long_weekend_length"),
+        (Fields::CLUSTER_INDEX, "cluster_index"),
+        (Fields::NAME_EN, "name_en"),
+        (Fields::ERA, "era"),
+        (Fields::TIME, "time"),
+    ];
+
+    pub fn contains(self, other: Fields) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn remove(&mut self, other: Fields) {
+        self.0 &= !other.0;
+    }
+
+    fn names(self) -> Vec<&'static str> {
+        Fields::NAMES.iter().filter(|(f, _)| self.contains(*f)).map(|(_, name)| *name).collect()
+    }
+}
+
+// month and day, the fields output before selection existed
+impl Default for Fields {
+    fn default() -> Self {
+        Fields::NONE
+    }
+}
+
+impl BitOr for Fields {
+    type Output = Fields;
+    fn bitor(self, rhs: Fields) -> Fields {
+        Fields(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Fields {
+    fn bitor_assign(&mut self, rhs: Fields) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// The holidays of one year, indexed by date for membership checks.
+#[derive(Debug, Clone)]
+pub struct HolidaySet {
+    year: u32,
+    days: BTreeMap<NaiveDate, String>,
+}
+
+impl HolidaySet {
+    pub fn for_year(year: impl Into<Year>) -> Result<Self> {
+        let year = year.into().to_u32()?;
+        Ok(HolidaySet {
+            year,
+            days: holiday_map(year)?,
+        })
+    }
+
+    pub fn year(&self) -> u32 {
+        self.year
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.days.contains_key(&date)
+    }
+
+    /// The holiday name on `date`, preferring the statutory name as in `holiday_map`
+    pub fn name(&self, date: NaiveDate) -> Option<&str> {
+        self.days.get(&date).map(String::as_str)
+    }
+}
+
+/// Successive business days, neither weekends nor holidays, after a date; see `business_days_from`.
+/// Loads the holidays of each year on demand, so it crosses year boundaries.
+#[derive(Debug, Clone)]
+pub struct BusinessDays {
+    current: NaiveDate,
+    holidays: Option<HolidaySet>,
+}
+
+impl BusinessDays {
+    fn is_business_day(&mut self, date: NaiveDate) -> Result<bool> {
+        if matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+            return Ok(false);
+        }
+        let year = u32::try_from(date.year()).map_err(|_| HolidayError::YearOutOfRange(date.year().into()))?;
+        let holidays = match self.holidays.take() {
+            Some(set) if set.year() == year => set,
+            _ => HolidaySet::for_year(year)?,
+        };
+        let holiday = holidays.contains(date);
+        self.holidays = Some(holidays);
+        Ok(!holiday)
+    }
+}
+
+impl Iterator for BusinessDays {
+    type Item = Result<NaiveDate>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.current = self.current.succ_opt()?;
+            match self.is_business_day(self.current) {
+                Ok(true) => return Some(Ok(self.current)),
+                Ok(false) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Business days after `start`, lazily: `business_days_from(date).take(5)` are the next five.
+pub fn business_days_from(start: NaiveDate) -> BusinessDays {
+    BusinessDays {
+        current: start,
+        holidays: None,
+    }
+}
+
+/// The first business day after `date`, skipping weekends and holidays.
+pub fn next_business_day(date: NaiveDate) -> Result<NaiveDate> {
+    business_days_from(date).next().unwrap_or_else(|| Err(anyhow!("no business day after {}", date)))
+}
+
+/// The `n`th business day (1-based) of `month` in `year`, or `None` if the month has fewer.
+pub fn nth_business_day_of_month(year: impl Into<Year>, month: u32, n: u32) -> Result<Option<NaiveDate>> {
+    let year = year.into().to_u32()?;
+    let first = i32::try_from(year).ok()
+        .and_then(|y| NaiveDate::from_ymd_opt(y, month, 1))
+        .ok_or_else(|| anyhow!("no month {} in {}", month, year))?;
+    let mut count = 0;
+    for date in business_days_from(first - Duration::days(1)) {
+        let date = date?;
+        if date.month() != month {
+            break;
+        }
+        count += 1;
+        if count == n {
+            return Ok(Some(date));
+        }
+    }
+    Ok(None)
+}
+
+/// `date` if it is a business day, otherwise the closest business day before it,
+/// e.g. for salaries paid on the 25th or the preceding business day.
+pub fn business_day_on_or_before(date: NaiveDate) -> Result<NaiveDate> {
+    let mut days = business_days_from(date);
+    let mut current = date;
+    while !days.is_business_day(current)? {
+        current = current.pred_opt().ok_or_else(|| anyhow!("no business day on or before {}", date))?;
+    }
+    Ok(current)
+}
+
+/// Order of the holidays in the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    #[default]
+    Ascending,
+    /// Most recent first, e.g. for feeds
+    Descending,
+}
+
+/// Unit of the `time` field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeUnit {
+    #[default]
+    Seconds,
+    /// As JavaScript's `Date` takes it
+    Millis,
+}
+
+/// Builds the formatted holiday list with optional fields.
+#[derive(Debug, Clone)]
+pub struct OutputBuilder {
+    format: OutputFormat,
+    fields: Fields,
+    substitute_naming: SubstituteNaming,
+    sort: SortOrder,
+    include_equinoxes: bool,
+    equinox_source: EquinoxSource,
+    time_unit: TimeUnit,
+    bom: bool,
+}
+
+// Byte order mark prepended to the CSV output by `with_bom`
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+// UTC offset of Japan Standard Time, used to map instants onto Japanese calendar days.
+const JST_OFFSET_SECS: i32 = 9 * 3600;
+
+// The third Monday of September, which can sandwich a 国民の休日 with 秋分の日
+const RESPECT_FOR_THE_AGED_DAY_NAME: &str = "敬老の日";
+
+pub fn holiday(format:OutputFormat, year: impl Into<Year>)-> Result<String, Error> {
+    OutputBuilder::new(format).build(year)
+}
+
+impl OutputBuilder {
+    pub fn new(format: OutputFormat) -> Self {
+        OutputBuilder {
+            format,
+            fields: Fields::default(),
+            substitute_naming: SubstituteNaming::default(),
+            sort: SortOrder::default(),
+            include_equinoxes: true,
+            equinox_source: EquinoxSource::default(),
+            time_unit: TimeUnit::default(),
+            bom: false,
+        }
+    }
+
+    /// Omits 春分の日 and 秋分の日 when `false`, e.g. for consumers handling the equinoxes separately.
+    ///
+    /// Holidays derived from an equinox, its substitute or a 国民の休日 next to it, are still listed;
+    /// filter on `Fields::CONFIDENCE` to drop predicted ones as well.
+    pub fn include_equinoxes(mut self, enabled: bool) -> Self {
+        self.include_equinoxes = enabled;
+        self
+    }
+
+    /// Takes the equinox days from `source`, e.g. `EquinoxSource::OfficialTable` to list announced days only.
+    pub fn equinox_source(mut self, source: EquinoxSource) -> Self {
+        self.equinox_source = source;
+        self
+    }
+
+    /// Starts the CSV output with the UTF-8 BOM (`EF BB BF`), which Excel needs to detect UTF-8.
+    /// Other formats are not affected.
+    pub fn with_bom(mut self, enabled: bool) -> Self {
+        self.bom = enabled;
+        self
+    }
 
-fn substitute_adjustment(data: &mut Vec<Holiday>) {
-   let mut i:usize = 0;
-   while i < data.len() {
-        // if it a Sunday
-        if data[i].date.weekday() == Weekday::Sun {
-            let mut last_holiday_date = data[i].date;
-            while let Some(next_holiday) = data.get(i+1) {
-                if next_holiday.date == last_holiday_date + Duration::days(1) {
-                    i += 1;
-                    last_holiday_date = next_holiday.date;
-                } else {
-                    break;
+    /// Orders the holidays by date, ascending unless `SortOrder::Descending` is given.
+    pub fn sort(mut self, order: SortOrder) -> Self {
+        self.sort = order;
+        self
+    }
+
+    /// Labels substitute holidays with `naming` instead of `振替休日(元の祝日)`.
+    pub fn substitute_naming(mut self, naming: SubstituteNaming) -> Self {
+        self.substitute_naming = naming;
+        self
+    }
+
+    /// Selects exactly which optional fields are output (default: `Fields::NONE`).
+    pub fn fields(mut self, fields: Fields) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    /// Adds `month` and `day`, the month and day of the date as integers.
+    pub fn with_month_day(mut self, enabled: bool) -> Self {
+        self.set_field(Fields::MONTH | Fields::DAY, enabled);
+        self
+    }
+
+    /// Adds `datetime_rfc3339`, the holiday's midnight in JST (`2024-01-01T00:00:00+09:00`).
+    pub fn with_rfc3339(mut self, enabled: bool) -> Self {
+        self.set_field(Fields::RFC3339, enabled);
+        self
+    }
+
+    /// Adds `name_en`, the English name, next to the Japanese `name`.
+    pub fn with_bilingual(mut self, enabled: bool) -> Self {
+        self.set_field(Fields::NAME_EN, enabled);
+        self
+    }
+
+    /// Adds `era`, the Japanese era year of the date, e.g. `令和6年`.
+    pub fn with_era(mut self, enabled: bool) -> Self {
+        self.set_field(Fields::ERA, enabled);
+        self
+    }
+
+    /// Adds `time`, the holiday's midnight in JST as Unix time, in seconds unless `time_unit` says otherwise.
+    pub fn with_time(mut self, enabled: bool) -> Self {
+        self.set_field(Fields::TIME, enabled);
+        self
+    }
+
+    /// Emits `time` in `unit`, e.g. `TimeUnit::Millis` for JavaScript's `Date`.
+    pub fn time_unit(mut self, unit: TimeUnit) -> Self {
+        self.time_unit = unit;
+        self
+    }
+
+    fn set_field(&mut self, field: Fields, enabled: bool) {
+        if enabled {
+            self.fields |= field;
+        } else {
+            self.fields.remove(field);
+        }
+    }
+
+    pub fn build(&self, year: impl Into<Year>) -> Result<String> {
+        Ok(String::from_utf8(self.build_bytes(year)?)?)
+    }
+
+    /// Same output as [`OutputBuilder::build`], as UTF-8 bytes.
+    pub fn build_bytes(&self, year: impl Into<Year>) -> Result<Vec<u8>> {
+        let mut bytes: Vec<u8> = Vec::new();
+        self.write_to(year, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Serializes the output directly into `writer`.
+    pub fn write_to<W: Write>(&self, year: impl Into<Year>, writer: W) -> Result<()> {
+        self.format_by_holidays(self.holidays(year.into())?, writer)
+    }
+
+    // The holidays of `year` selected by the builder
+    fn holidays(&self, year: Year) -> Result<Vec<Holiday>> {
+        let calendar = JapanNationalCalendar { naming: self.substitute_naming, equinox_source: self.equinox_source };
+        let mut days = calendar.holidays(year.to_u32()?)?;
+        if !self.include_equinoxes {
+            days.retain(|h| h.category != HolidayCategory::Equinox);
+        }
+        Ok(days)
+    }
+
+    fn shape(&self, holiday: Holiday, references: &HashMap<String, String>) -> HolidayShapedItem {
+        let fields = self.fields;
+        let law_reference = if fields.contains(Fields::LAW_REFERENCE) {
+            match holiday.category {
+                HolidayCategory::Substitute => Some(SUBSTITUTE_HOLIDAY_LAW_REFERENCE.to_string()),
+                HolidayCategory::National => Some(NATIONAL_HOLIDAY_LAW_REFERENCE.to_string()),
+                HolidayCategory::Equinox => Some(EQUINOX_LAW_REFERENCE.to_string()),
+                _ => references.get(&holiday.name).cloned(),
+            }
+        } else {
+            None
+        };
+        HolidayShapedItem {
+            law_reference,
+            confidence: fields.contains(Fields::CONFIDENCE).then_some(holiday.confidence),
+            month: fields.contains(Fields::MONTH).then(|| holiday.date.month()),
+            day: fields.contains(Fields::DAY).then(|| holiday.date.day()),
+            datetime_rfc3339: fields.contains(Fields::RFC3339).then(|| jst_midnight(holiday.date).to_rfc3339()),
+            category: fields.contains(Fields::CATEGORY).then_some(holiday.category),
+            // Needs the neighbouring holidays, filled in by `format_by_holidays`
+            long_weekend_length: None,
+            cluster_index: None,
+            name_en: fields.contains(Fields::NAME_EN).then(|| english_label(&holiday)).flatten(),
+            era: fields.contains(Fields::ERA).then(|| era_name(holiday.date)).flatten(),
+            time: fields.contains(Fields::TIME).then(|| {
+                let seconds = jst_midnight(holiday.date).timestamp();
+                match self.time_unit {
+                    TimeUnit::Seconds => seconds,
+                    TimeUnit::Millis => seconds * 1000,
                 }
+            }),
+            name: holiday.name,
+            date: holiday.date,
+            substitute: holiday.substitute,
+        }
+    }
+
+    /// The items of `year` as they are serialized, with the selected fields and sort order.
+    pub fn build_items(&self, year: impl Into<Year>) -> Result<Vec<HolidayShapedItem>> {
+        self.shape_all(self.holidays(year.into())?)
+    }
+
+    fn shape_all(&self, holidays: Vec<Holiday>) -> Result<Vec<HolidayShapedItem>> {
+        // The base data's law references by holiday name, only loaded when they are output
+        let references: HashMap<String, String> = if self.fields.contains(Fields::LAW_REFERENCE) {
+            schedule()?.iter().filter_map(|d| Some((d.name.clone(), d.law_reference.clone()?)))
+                .chain(one_off_schedule()?.iter().map(|d| (d.name.clone(), d.law_reference.clone())))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+        let positions = if self.fields.contains(Fields::LONG_WEEKEND) || self.fields.contains(Fields::CLUSTER_INDEX) {
+            day_off_positions(&holidays)
+        } else {
+            HashMap::new()
+        };
+        let mut m: Vec<HolidayShapedItem> = holidays.into_iter().map(|h| self.shape(h, &references)).collect();
+        for d in &mut m {
+            let position = positions.get(&d.date).copied();
+            if self.fields.contains(Fields::LONG_WEEKEND) {
+                d.long_weekend_length = position.map(|(_, len)| len).filter(|len| *len >= 3);
             }
-            let mut sub_date = last_holiday_date + Duration::days(1);
-            while data.iter().any(|h:&Holiday| h.date == sub_date) {
-                sub_date = sub_date + Duration::days(1);
+            if self.fields.contains(Fields::CLUSTER_INDEX) {
+                d.cluster_index = position.filter(|(_, len)| *len >= 2);
             }
+        }
+        if self.sort == SortOrder::Descending {
+            m.reverse();
+        }
+        Ok(m)
+    }
 
-            data.push(Holiday {
-                name: format!("振替休日({})", data[i].name),
-                date: sub_date,
-                substitute: true,
-            });
+    fn format_by_holidays<W: Write>(&self, holidays: Vec<Holiday>, mut writer: W) -> Result<()> {
+        let m = self.shape_all(holidays)?;
+        match self.format {
+            OutputFormat::CSV => {
+                if self.bom {
+                    writer.write_all(UTF8_BOM)?;
+                }
+                write!(writer, "name,date,substitute")?;
+                for name in self.fields.names() {
+                    write!(writer, ",{}", name)?;
+                }
+                writeln!(writer)?;
+                for d in m {
+                    write!(writer, "{},{},{}", d.name, d.date, d.substitute)?;
+                    // A selected field without a value, e.g. a holiday outside any long weekend, stays an empty column
+                    for ((field, _), v) in Fields::NAMES.iter().zip(d.optional_values()) {
+                        if self.fields.contains(*field) {
+                            write!(writer, ",{}", v.unwrap_or_default())?;
+                        }
+                    }
+                    writeln!(writer)?;
+                }
+            },
+            OutputFormat::JSON => {
+                serde_json::to_writer_pretty(writer, &m)?;
+            },
+            OutputFormat::YAML => {
+                serde_yaml::to_writer(writer, &m)?;
+            },
+            OutputFormat::NDJSON => {
+                for d in &m {
+                    serde_json::to_writer(&mut writer, d)?;
+                    writeln!(writer)?;
+                }
+            }
+            // TOML has no top-level arrays, so the list is wrapped in a table
+            #[cfg(feature = "toml")]
+            OutputFormat::TOML => {
+                #[derive(Serialize)]
+                struct HolidayDocument {
+                    holidays: Vec<HolidayShapedItem>,
+                }
+                writer.write_all(toml::to_string(&HolidayDocument { holidays: m })?.as_bytes())?;
+            }
         }
-        i += 1;
-   }
+        Ok(())
+    }
 }
 
+/// The holidays of `year` as a JSON value, `{"year": 2024, "data_hash": "...", "holidays": [...]}`, for embedding
+/// into a larger document. `data_hash` identifies the embedded data, see `timebase::data_hash`.
+///
+/// The items carry the default fields, as in the JSON output.
+pub fn holidays_value(year: impl Into<Year>) -> Result<serde_json::Value> {
+    let year = year.into().to_u32()?;
+    let items = OutputBuilder::new(OutputFormat::JSON).build_items(year)?;
+    Ok(serde_json::json!({ "year": year, "data_hash": data_hash(), "holidays": serde_json::to_value(items)? }))
+}
 
-fn pick_exuinox_from_year(year:u32) -> Vec<Holiday> {
-    if year < 2020 || year > 2050 {
-        return Vec::new();
+/// The holidays of `start_year` through `end_year` (both inclusive) as one JSON document keyed by year,
+/// `{"2023": [...], "2024": [...]}`, each value being the JSON output of that year.
+///
+/// Empty `{}` when `start_year` is after `end_year`.
+pub fn holidays_range_json(start_year: impl Into<Year>, end_year: impl Into<Year>) -> Result<String> {
+    let start_year = start_year.into().to_u32()?;
+    let end_year = end_year.into().to_u32()?;
+    let builder = OutputBuilder::new(OutputFormat::JSON);
+    let mut years: BTreeMap<String, Vec<HolidayShapedItem>> = BTreeMap::new();
+    for year in start_year..=end_year {
+        years.insert(year.to_string(), builder.build_items(year)?);
     }
-    let equinoxes = get_equinox_dates().unwrap();
-    let target = equinoxes.into_iter().find(|x| x.year == year);
-    let mut return_value: Vec<Holiday> = Vec::new();
-    match target {
-        Some(v) => {
-            v.equinox.into_iter().for_each(|x| {
-                return_value.push(Holiday {
-                    name: x.name,
-                    date: NaiveDate::parse_from_str(&format!("{}/{}", year, x.date).to_string(), "%Y/%m/%d").unwrap(),
-                    substitute: false,
-                });
-            })
-        },
-        None => {},
+    Ok(serde_json::to_string_pretty(&years)?)
+}
+
+/// The JSON output of `year` as bytes, serialized without an intermediate `String`.
+pub fn holidays_json_bytes(year: impl Into<Year>) -> Result<Vec<u8>> {
+    let year = year.into().to_u32()?;
+    OutputBuilder::new(OutputFormat::JSON).build_bytes(year)
+}
+
+/// Holidays of `year` as plain `(year, month, day, name)` tuples, for callers without `chrono`.
+pub fn holidays_ymd(year: impl Into<Year>) -> Result<Vec<(i32, u32, u32, String)>> {
+    let year = year.into().to_u32()?;
+    Ok(holidays(year)?
+        .into_iter()
+        .map(|h| (h.date.year(), h.date.month(), h.date.day(), h.name))
+        .collect())
+}
+
+/// A source of holidays.
+pub trait Calendar {
+    // Holidays of `year`, sorted by date
+    fn holidays(&self, year: u32) -> Result<Vec<Holiday>>;
+
+    // The name of the holiday that `date` substitutes for, if `date` is a substitute holiday.
+    // The source can be in the previous year, e.g. a December 31 on Sunday compensated in January.
+    fn substitute_source(&self, date: NaiveDate) -> Result<Option<String>> {
+        let year = match u32::try_from(date.year()) {
+            Ok(v) => v,
+            Err(_) => return Ok(None),
+        };
+        Ok(self.holidays(year)?
+            .into_iter()
+            .find(|h| h.date == date && h.substitute)
+            .and_then(|h| h.source))
     }
+}
 
-    return_value
+/// The national holidays of Japan, with substitute holidays labelled by `naming` and the equinox days
+/// taken from `equinox_source`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JapanNationalCalendar {
+    pub naming: SubstituteNaming,
+    pub equinox_source: EquinoxSource,
+}
 
+impl Calendar for JapanNationalCalendar {
+    fn holidays(&self, year: u32) -> Result<Vec<Holiday>> {
+        compute_holidays(year, year, self.equinox_source, &rules_with(self.naming))
+    }
+}
 
+/// The union of several calendars, e.g. the national holidays and a company calendar.
+///
+/// Substitute holidays and 国民の休日 of the sources are dropped, and the rules of the national holidays
+/// are applied again over the union: a source's holiday on Sunday gets a substitute that avoids every other
+/// source's holidays, and a day between holidays of two sources becomes 国民の休日. Holidays of several
+/// sources on the same date are merged into one named e.g. `元旦・年始休暇`, in the order of the sources.
+/// As for the national holidays, the December holidays of the previous year take part, so their substitutes
+/// in January are listed under `year`.
+pub struct CombinedCalendar {
+    pub sources: Vec<Box<dyn Calendar>>,
 }
 
-// for base dates
-fn prepara(year: u32)->Vec<Holiday> {
-    let dataset = get_schedule().unwrap();
-    let mut days: Vec<Holiday> = Vec::new();
-    for d in dataset {
-        if d.relative {
-            let relative_date = get_relative_date(year, d.condition.unwrap()).unwrap();
-            days.push(Holiday {
-                name: d.name,
-                date: relative_date.format("%Y-%m-%d").to_string().parse::<NaiveDate>().unwrap(),
-                substitute: false,
-            })
-        } else {
-            days.push(Holiday {
-                name: d.name,
-                date: NaiveDate::parse_from_str(&format!("{}/{}", year, d.date.unwrap()).to_string()
-                , "%Y/%m/%d").unwrap(),
-                substitute: false,
-            })
+impl Calendar for CombinedCalendar {
+    fn holidays(&self, year: u32) -> Result<Vec<Holiday>> {
+        let mut union: Vec<Holiday> = Vec::new();
+        for source in &self.sources {
+            let mut days = source.holidays(year)?;
+            if let Some(previous) = year.checked_sub(1) {
+                days.extend(source.holidays(previous)?.into_iter().filter(|h| h.date.month() == 12));
+            }
+            union.extend(days.into_iter().filter(|h| !h.substitute && h.category != HolidayCategory::National));
         }
+        // The sort is stable, so holidays sharing a date keep the order of the sources for the merge
+        Ok(apply_within_year(year, union, &rules_with(SubstituteNaming::default())))
     }
-    days
 }
 
-// for relative date comvart Datetime
-fn get_relative_date(year: u32, condition: Condition)-> Option<DateTime<Local>> {
-    let month = get_month_num_from_string(&condition.month).unwrap();
-    let weekday = get_weekday_from_string(&condition.weekday).unwrap();
-    let n = condition.n;
-    let mut dates: Vec<DateTime<Local>> = Vec::new();
-    let mut day:DateTime<Local> = Local.with_ymd_and_hms(year as i32, month, 1, 0, 0, 0).unwrap();
+/// Holidays of `year`, sorted by date.
+pub fn holidays(year: impl Into<Year>) -> Result<Vec<Holiday>> {
+    JapanNationalCalendar::default().holidays(year.into().to_u32()?)
+}
+
+/// Holidays of each year of `years`, computed lazily one year at a time, so that e.g. centuries of data can be
+/// streamed to disk without holding every year in memory.
+///
+/// The base data is parsed once and shared by all years. A failing year yields its error and iteration goes on.
+pub fn holidays_range_iter(years: Range<i32>) -> impl Iterator<Item = Result<(i32, Vec<Holiday>)>> {
+    let calendar = JapanNationalCalendar::default();
+    years.map(move |year| Ok((year, calendar.holidays(Year::from(year).to_u32()?)?)))
+}
 
-    while day.month() == month {
-        if day.weekday() == weekday {
-            dates.push(day);
-        }
-        day = day + Duration::days(1);
+/// Number of holidays of `year`, the length of the default list: substitutes, 国民の休日 and equinoxes included.
+///
+/// Computes the holidays without shaping or serializing them.
+pub fn holidays_count(year: impl Into<Year>) -> Result<usize> {
+    let year = year.into().to_u32()?;
+    Ok(holidays(year)?.len())
+}
+
+// Holidays of `year` under the rules in force in `law_year`, usually `year` itself.
+fn compute_holidays(year: u32, law_year: u32, source: EquinoxSource, rules: &[Box<dyn Rule>]) -> Result<Vec<Holiday>> {
+    Ok(apply_within_year(year, placed_holidays(year, law_year, source)?, rules))
+}
+
+// The holidays of `year` and the previous December before applying the substitute and national holiday rules.
+fn placed_holidays(year: u32, law_year: u32, source: EquinoxSource) -> Result<Vec<Holiday>> {
+    //List of holidays stipulated in the Holidays Act
+    let mut m = statutory_holidays(year, law_year)?;
+    let e= pick_exuinox_from_year(year, law_year, source)?;
+    m.extend(e);
+
+    //the previous December, whose substitutes can fall in January
+    if let Some(previous) = year.checked_sub(1) {
+        m.extend(statutory_holidays(previous, law_year)?.into_iter().filter(|h| h.date.month() == 12));
+    }
+    Ok(m)
+}
+
+// The holidays of the base data in force in `law_year`, placed in `year`, with the relocations of
+// `special_holidays.csv` and the holidays of `one_off_holidays.csv` applied. Both are special measures
+// for their year, so a law year before it does not know them yet.
+fn statutory_holidays(year: u32, law_year: u32) -> Result<Vec<Holiday>> {
+    let mut days = prepare_holidays(year, schedule()?.iter().filter(|d| d.in_force(law_year)))?;
+    if law_year >= year {
+        relocate_special_holidays(year, &mut days, special_schedule()?)?;
+        days.extend(one_off_holidays(year, one_off_schedule()?)?);
     }
+    Ok(days)
+}
 
-    Some(dates[n as usize -1])
+// The one-off holidays held in `year`.
+fn one_off_holidays(year: u32, one_off: &[OneOffHoliday]) -> Result<Vec<Holiday>> {
+    one_off.iter()
+        .filter(|d| d.year == year)
+        .map(|d| Ok(Holiday {
+            name: d.name.clone(),
+            date: parse_month_day(year, &d.date)
+                .map_err(|e| anyhow!("invalid date {:?} for {} in {}: {}", d.date, d.name, year, e))?,
+            substitute: false,
+            category: HolidayCategory::Special,
+            source: None,
+            confidence: Confidence::Enacted,
+        }))
+        .collect()
 }
 
-fn get_weekday_from_string(char: &str)-> Option<Weekday> {
-    match char.trim().to_lowercase().as_str() {
-        "monday" | "mon" => Some(Weekday::Mon),
-        "tuesday" | "tue" => Some(Weekday::Tue),
-        "wednesday" | "wed" => Some(Weekday::Wed),
-        "thursday" | "thu" => Some(Weekday::Thu),
-        "friday" | "fri" => Some(Weekday::Fri),
-        "saturday" | "sat" => Some(Weekday::Sat),
-        "sunday" | "sun" => Some(Weekday::Sun),
-        _ => None,
+// Moves the holidays relocated in `year`, e.g. 海の日 to July 22 for the 2021 Olympics.
+// A relocation naming no holiday of `days` is an error, as the data would be silently ignored.
+fn relocate_special_holidays(year: u32, days: &mut [Holiday], special: &[SpecialHoliday]) -> Result<()> {
+    for s in special.iter().filter(|s| s.year == year) {
+        let date = parse_month_day(year, &s.date)
+            .map_err(|e| anyhow!("invalid date {:?} for {} in {}: {}", s.date, s.name, year, e))?;
+        let holiday = days.iter_mut()
+            .find(|h| h.name == s.name)
+            .ok_or_else(|| anyhow!("{} is relocated in {} but not a holiday of that year", s.name, year))?;
+        trace_step!(name = %s.name, from = %holiday.date, to = %date, "holiday relocated");
+        holiday.date = date;
     }
+    Ok(())
 }
-fn get_month_num_from_string(char: &str) -> Option<u32> {
-    match char.trim().to_lowercase().as_str() {
-        "january" | "jan" => Some(1),
-        "february" | "feb" => Some(2),
-        "march" | "mar" => Some(3),
-        "april" | "apr" => Some(4),
-        "may" => Some(5),
-        "june" | "jun" => Some(6),
-        "july" | "jul" => Some(7),
-        "august" | "aug" => Some(8),
-        "september" | "sep" => Some(9),
-        "october" | "oct" => Some(10),
-        "november" | "nov" => Some(11),
-        "december" | "dec" => Some(12),
-        _ => None,
+
+// Sorts `days`, applies `rules` and keeps the holidays dated in `year`.
+// `days` can include the end of the previous year, so that substitutes crossing New Year are found, while
+// substitutes of `year` falling in the next year are left to that year's list.
+fn apply_within_year(year: u32, mut days: Vec<Holiday>, rules: &[Box<dyn Rule>]) -> Vec<Holiday> {
+    days.sort_by_key(|a| a.date);
+    for rule in rules {
+        rule.apply(&mut days);
     }
+    days.retain(|h| u32::try_from(h.date.year()) == Ok(year));
+    days
 }
 
-#[cfg(test)]
-pub mod test {
-    use pretty_assertions::assert_eq;
-    #[test]
-    pub fn test_holiday_output_yml() {
-        let year = 2024;
-        let expected = "- name: 元旦\n  date: 2024-01-01\n  substitute: false\n- name: 成人の日\n  date: 2024-01-08\n  substitute: false\n- name: 建国記念の日\n  date: 2024-02-11\n  substitute: false\n- name: 振替休日(建国記念の日)\n  date: 2024-02-12\n  substitute: true\n- name: 天皇誕生日\n  date: 2024-02-23\n  substitute: false\n- name: 春分の日\n  date: 2024-03-20\n  substitute: false\n- name: 昭和の日\n  date: 2024-04-29\n  substitute: false\n- name: 憲法記念日\n  date: 2024-05-03\n  substitute: false\n- name: みどりの日\n  date: 2024-05-04\n  substitute: false\n- name: こどもの日\n  date: 2024-05-05\n  substitute: false\n- name: 振替休日(こどもの日)\n  date: 2024-05-06\n  substitute: true\n- name: 海の日\n  date: 2024-07-15\n  substitute: false\n- name: 山の日\n  date: 2024-08-11\n  substitute: false\n- name: 振替休日(山の日)\n  date: 2024-08-12\n  substitute: true\n- name: 敬老の日\n  date: 2024-09-16\n  substitute: false\n- name: 秋分の日\n  date: 2024-09-22\n  substitute: false\n- name: 振替休日(秋分の日)\n  date: 2024-09-23\n  substitute: true\n- name: スポーツの日\n  date: 2024-10-14\n  substitute: false\n- name: 文化の日\n  date: 2024-11-03\n  substitute: false\n- name: 振替休日(文化の日)\n  date: 2024-11-04\n  substitute: true\n- name: 勤労感謝の日\n  date: 2024-11-23\n  substitute: false\n";
-        let format = super::OutputFormat::YAML;
-        let result = super::holiday(format, year).unwrap();
-        assert_eq!(result, expected);
+/// Applies the substitute and national holiday rules of `year` to a user's own holiday list, e.g. a company
+/// calendar, laid out as `date,name` with `YYYY-MM-DD` dates, and returns it as CSV with the derived rows added.
+///
+/// Rows outside `year` are dropped, except that December holidays of the previous year still yield their
+/// substitutes in January. The list is used as is: national holidays count only if it includes them.
+pub fn apply_substitutes_to_csv(csv: &str, year: impl Into<Year>) -> Result<String> {
+    let year = year.into().to_u32()?;
+    let mut days: Vec<Holiday> = Vec::new();
+    for custom in parse_custom_holidays(csv.as_bytes())? {
+        let date = NaiveDate::parse_from_str(&custom.date, "%Y-%m-%d")
+            .map_err(|e| anyhow!("invalid date {:?} for {}: {}", custom.date, custom.name, e))?;
+        let previous_december = date.month() == 12 && i64::from(date.year()) + 1 == i64::from(year);
+        if i64::from(date.year()) == i64::from(year) || previous_december {
+            days.push(Holiday {
+                name: custom.name,
+                date,
+                substitute: false,
+                category: HolidayCategory::Fixed,
+                source: None,
+                confidence: Confidence::Enacted,
+            });
+        }
     }
-    #[test]
-    pub fn test_holiday_output_json() {
-        let year = 2024;
-        let expected = "[\n  {\n    \"name\": \"元旦\",\n    \"date\": \"2024-01-01\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"成人の日\",\n    \"date\": \"2024-01-08\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"建国記念の日\",\n    \"date\": \"2024-02-11\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"振替休日(建国記念の日)\",\n    \"date\": \"2024-02-12\",\n    \"substitute\": true\n  },\n  {\n    \"name\": \"天皇誕生日\",\n    \"date\": \"2024-02-23\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"春分の日\",\n    \"date\": \"2024-03-20\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"昭和の日\",\n    \"date\": \"2024-04-29\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"憲法記念日\",\n    \"date\": \"2024-05-03\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"みどりの日\",\n    \"date\": \"2024-05-04\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"こどもの日\",\n    \"date\": \"2024-05-05\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"振替休日(こどもの日)\",\n    \"date\": \"2024-05-06\",\n    \"substitute\": true\n  },\n  {\n    \"name\": \"海の日\",\n    \"date\": \"2024-07-15\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"山の日\",\n    \"date\": \"2024-08-11\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"振替休日(山の日)\",\n    \"date\": \"2024-08-12\",\n    \"substitute\": true\n  },\n  {\n    \"name\": \"敬老の日\",\n    \"date\": \"2024-09-16\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"秋分の日\",\n    \"date\": \"2024-09-22\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"振替休日(秋分の日)\",\n    \"date\": \"2024-09-23\",\n    \"substitute\": true\n  },\n  {\n    \"name\": \"スポーツの日\",\n    \"date\": \"2024-10-14\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"文化の日\",\n    \"date\": \"2024-11-03\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"振替休日(文化の日)\",\n    \"date\": \"2024-11-04\",\n    \"substitute\": true\n  },\n  {\n    \"name\": \"勤労感謝の日\",\n    \"date\": \"2024-11-23\",\n    \"substitute\": false\n  }\n]";
-        let format = super::OutputFormat::JSON;
-        let result = super::holiday(format, year).unwrap();
-        assert_eq!(result, expected)
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(["date", "name"])?;
+    for h in apply_within_year(year, days, &rules_with(SubstituteNaming::default())) {
+        writer.write_record([h.date.to_string(), h.name])?;
     }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
 
-    #[test]
-    pub fn test_holiday_output_csv() {
-        let year = 2024;
-        let expected = "name,date,substitute\n元旦,2024-01-01,false\n成人の日,2024-01-08,false\n建国記念の日,2024-02-11,false\n振替休日(建国記念の日),2024-02-12,true\n天皇誕生日,2024-02-23,false\n春分の日,2024-03-20,false\n昭和の日,2024-04-29,false\n憲法記念日,2024-05-03,false\nみどりの日,2024-05-04,false\nこどもの日,2024-05-05,false\n振替休日(こどもの日),2024-05-06,true\n海の日,2024-07-15,false\n山の日,2024-08-11,false\n振替休日(山の日),2024-08-12,true\n敬老の日,2024-09-16,false\n秋分の日,2024-09-22,false\n振替休日(秋分の日),2024-09-23,true\nスポーツの日,2024-10-14,false\n文化の日,2024-11-03,false\n振替休日(文化の日),2024-11-04,true\n勤労感謝の日,2024-11-23,false\n";
-        let format = super::OutputFormat::CSV;
-        let result = super::holiday(format, year).unwrap();
-        assert_eq!(result, expected)
+/// Holidays from `start` through `end` (both inclusive), sorted by date.
+///
+/// The span may reach beyond `Year::MIN..=Year::MAX`; those years contribute no holidays.
+pub fn holidays_between(start: NaiveDate, end: NaiveDate) -> Result<Vec<Holiday>> {
+    let mut days: Vec<Holiday> = Vec::new();
+    for year in supported_years(start, end) {
+        let year_holidays = holidays(year)?;
+        days.extend(year_holidays.into_iter().filter(|h| h.date >= start && h.date <= end));
     }
+    Ok(days)
+}
 
+/// Holidays from `start` through `end` (both inclusive) falling Monday to Friday, substitutes included:
+/// the days that actually close offices, e.g. for a report of closures in a quarter.
+///
+/// Items carry the default fields.
+pub fn weekday_holidays_between(start: NaiveDate, end: NaiveDate) -> Result<Vec<HolidayShapedItem>> {
+    let builder = OutputBuilder::new(OutputFormat::JSON);
+    Ok(holidays_between(start, end)?
+        .into_iter()
+        .filter(|h| !is_weekend(h.date))
+        .map(|h| builder.shape(h, &HashMap::new()))
+        .collect())
+}
+
+/// Whether any holiday falls from `start` through `end` (both inclusive).
+///
+/// Stops at the first holiday found instead of collecting the whole span. Years outside
+/// `Year::MIN..=Year::MAX` have no holidays, as in [`holidays_between`].
+pub fn contains_holiday(start: NaiveDate, end: NaiveDate) -> Result<bool> {
+    for year in supported_years(start, end) {
+        if holidays(year)?.iter().any(|h| h.date >= start && h.date <= end) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+// The years from `start` through `end` within `Year::MIN..=Year::MAX`, empty if none.
+fn supported_years(start: NaiveDate, end: NaiveDate) -> std::ops::RangeInclusive<u32> {
+    let first = start.year().max(Year::MIN) as u32;
+    let last = end.year().clamp(Year::MIN - 1, Year::MAX) as u32;
+    first..=last
+}
+
+/// Holidays of the Japanese fiscal year `fy`: April 1 of `fy` through March 31 of `fy + 1`.
+///
+/// The March of `fy + 1` has no holidays for the last supported year, see [`holidays_between`].
+pub fn fiscal_year_holidays(fy: impl Into<Year>) -> Result<Vec<Holiday>> {
+    let fy = fy.into().to_u32()?;
+    let (start, end) = i32::try_from(fy)
+        .ok()
+        .and_then(|y| Some((NaiveDate::from_ymd_opt(y, 4, 1)?, NaiveDate::from_ymd_opt(y.checked_add(1)?, 3, 31)?)))
+        .ok_or_else(|| anyhow!("fiscal year {} is out of range", fy))?;
+    holidays_between(start, end)
+}
+
+/// Holidays of `year` keyed by month number (1-12).
+///
+/// Months without any holiday are omitted rather than present as empty vectors.
+pub fn holidays_grouped_by_month(year: impl Into<Year>) -> Result<BTreeMap<u32, Vec<Holiday>>> {
+    let year = year.into().to_u32()?;
+    let mut months: BTreeMap<u32, Vec<Holiday>> = BTreeMap::new();
+    for h in holidays(year)? {
+        months.entry(h.date.month()).or_default().push(h);
+    }
+    Ok(months)
+}
+
+/// Holidays of `year` keyed by ISO week as `(iso_year, week)`, for week-based planners.
+///
+/// The ISO year differs from `year` at the boundaries, e.g. 元旦 on Saturday 2022-01-01 is in week 52 of 2021.
+/// Weeks without any holiday are omitted. Items carry the default fields.
+pub fn holidays_by_iso_week(year: impl Into<Year>) -> Result<BTreeMap<(i32, u32), Vec<HolidayShapedItem>>> {
+    let year = year.into().to_u32()?;
+    let mut weeks: BTreeMap<(i32, u32), Vec<HolidayShapedItem>> = BTreeMap::new();
+    for h in OutputBuilder::new(OutputFormat::JSON).build_items(year)? {
+        let week = h.date.iso_week();
+        weeks.entry((week.year(), week.week())).or_default().push(h);
+    }
+    Ok(weeks)
+}
+
+/// Holidays of `year` whose date moves by rule: Happy-Monday holidays and the equinoxes.
+pub fn movable_holidays(year: impl Into<Year>) -> Result<Vec<Holiday>> {
+    let year = year.into().to_u32()?;
+    let mut days = holidays(year)?;
+    days.retain(|h| h.category.is_movable());
+    Ok(days)
+}
+
+/// Holidays of `year` that fall nowhere else in `start..=end`, e.g. the enthronement days of 2019 or the
+/// relocations for the 2020 Olympics. Items carry the default fields, as in the JSON output.
+///
+/// Holidays are compared by name and position: the month and day, or for Happy-Monday holidays the week of
+/// the month; equinoxes only by month, as their day shifts from year to year anyway.
+pub fn unique_holidays_in_range(year: impl Into<Year>, start: impl Into<Year>, end: impl Into<Year>) -> Result<Vec<HolidayShapedItem>> {
+    let year = year.into().to_u32()?;
+    let start = start.into().to_u32()?;
+    let end = end.into().to_u32()?;
+    let mut others = HashSet::new();
+    for y in (start..=end).filter(|y| *y != year) {
+        others.extend(holidays(y)?.iter().map(holiday_position));
+    }
+    let builder = OutputBuilder::new(OutputFormat::JSON);
+    Ok(holidays(year)?
+        .into_iter()
+        .filter(|h| !others.contains(&holiday_position(h)))
+        .map(|h| builder.shape(h, &HashMap::new()))
+        .collect())
+}
+
+// Where in its year a holiday falls, comparable across years, see `unique_holidays_in_range`.
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum HolidayPosition {
+    Day(u32, u32),
+    NthMonday(u32, u32),
+    Month(u32),
+}
+
+fn holiday_position(holiday: &Holiday) -> (String, HolidayPosition) {
+    let date = holiday.date;
+    let position = match holiday.category {
+        // A relocated Happy-Monday holiday, e.g. for the Olympics, is no longer on a Monday
+        HolidayCategory::HappyMonday if date.weekday() == Weekday::Mon => HolidayPosition::NthMonday(date.month(), (date.day() - 1) / 7 + 1),
+        HolidayCategory::Equinox => HolidayPosition::Month(date.month()),
+        _ => HolidayPosition::Day(date.month(), date.day()),
+    };
+    (holiday.name.clone(), position)
+}
+
+/// Whether month `month` of `year` has a Happy-Monday or equinox holiday, i.e. whether its holidays
+/// can differ from the fixed-date ones of another year beyond substitutes.
+pub fn month_has_movable_holiday(year: impl Into<Year>, month: u32) -> Result<bool> {
+    let year = year.into().to_u32()?;
+    Ok(movable_holidays(year)?.iter().any(|h| h.date.month() == month))
+}
+
+/// Every holiday name the crate can produce, independent of any year.
+///
+/// Substitute holidays are listed by their bare label `振替休日`; in output they carry
+/// the original holiday's name, e.g. `振替休日(こどもの日)`.
+pub fn all_holiday_names() -> Vec<&'static str> {
+    let mut names = schedule_names();
+    names.extend(one_off_schedule().into_iter().flatten().map(|d| d.name.as_str()));
+    names.extend([VERNAL_EQUINOX_NAME, AUTUMNAL_EQUINOX_NAME, NATIONAL_HOLIDAY_NAME, SUBSTITUTE_HOLIDAY_NAME]);
+    let mut seen = HashSet::new();
+    names.retain(|name| seen.insert(*name));
+    names
+}
+
+// First day of each era, latest first
+const ERAS: [(&str, (i32, u32, u32)); 3] = [("令和", (2019, 5, 1)), ("平成", (1989, 1, 8)), ("昭和", (1926, 12, 25))];
+
+/// The Japanese era year of `date`, e.g. `令和6年`, with `元年` for the first year, or `None` before 昭和.
+///
+/// The era is that of the day itself, so a year with a change of reign has two: 2019-04-29 is `平成31年`
+/// and 2019-05-01 `令和元年`.
+pub fn era_name(date: NaiveDate) -> Option<String> {
+    let (era, (first_year, _, _)) = ERAS.iter()
+        .find(|(_, (y, m, d))| NaiveDate::from_ymd_opt(*y, *m, *d).is_some_and(|first| first <= date))?;
+    Some(match date.year() - first_year + 1 {
+        1 => format!("{}元年", era),
+        n => format!("{}{}年", era, n),
+    })
+}
+
+/// The English name of the holiday `name`, as used by the Cabinet Office, e.g. `Coming of Age Day`
+/// for `成人の日`, or `None` for a name that is not a national holiday.
+pub fn english_name(name: &str) -> Option<&'static str> {
+    let english = match name {
+        "元旦" => "New Year's Day",
+        "成人の日" => "Coming of Age Day",
+        "建国記念の日" => "National Foundation Day",
+        "天皇誕生日" => "The Emperor's Birthday",
+        "みどりの日" => "Greenery Day",
+        "昭和の日" => "Showa Day",
+        "憲法記念日" => "Constitution Memorial Day",
+        "こどもの日" => "Children's Day",
+        "海の日" => "Marine Day",
+        "山の日" => "Mountain Day",
+        "敬老の日" => "Respect for the Aged Day",
+        "体育の日" => "Health and Sports Day",
+        "スポーツの日" => "Sports Day",
+        "文化の日" => "Culture Day",
+        "勤労感謝の日" => "Labor Thanksgiving Day",
+        "皇太子明仁親王の結婚の儀" => "Wedding Ceremony of Crown Prince Akihito",
+        "昭和天皇の大喪の礼" => "Funeral Ceremony of Emperor Showa",
+        "皇太子徳仁親王の結婚の儀" => "Wedding Ceremony of Crown Prince Naruhito",
+        "即位の日" => "Enthronement Day",
+        "即位礼正殿の儀" => "Enthronement Ceremony Day",
+        VERNAL_EQUINOX_NAME => "Vernal Equinox Day",
+        AUTUMNAL_EQUINOX_NAME => "Autumnal Equinox Day",
+        NATIONAL_HOLIDAY_NAME => "Citizens' Holiday",
+        SUBSTITUTE_HOLIDAY_NAME => "Substitute Holiday",
+        _ => return None,
+    };
+    Some(english)
+}
+
+// The English name of `holiday`; substitutes are named after their source whatever the `SubstituteNaming`
+fn english_label(holiday: &Holiday) -> Option<String> {
+    // Holidays merged by `SameDateMergeRule` are named after each of them
+    let english = |name: &str| -> Option<String> {
+        let names: Option<Vec<&str>> = name.split(MERGED_NAME_SEPARATOR).map(english_name).collect();
+        Some(names?.join(" / "))
+    };
+    let source = holiday.source.as_deref().filter(|_| holiday.category == HolidayCategory::Substitute);
+    match source {
+        Some(source) => Some(format!("{} ({})", english_name(SUBSTITUTE_HOLIDAY_NAME)?, english(source)?)),
+        None => english(&holiday.name),
+    }
+}
+
+/// Holidays of `year` as an ordered date → name map.
+///
+/// If two holidays share a date, the statutory (non-substitute) name is kept.
+pub fn holiday_map(year: impl Into<Year>) -> Result<BTreeMap<NaiveDate, String>> {
+    let year = year.into().to_u32()?;
+    let mut map = BTreeMap::new();
+    let (statutory, substitutes): (Vec<Holiday>, Vec<Holiday>) = holidays(year)?
+        .into_iter()
+        .partition(|h| !h.substitute);
+    for h in statutory.into_iter().chain(substitutes) {
+        map.entry(h.date).or_insert(h.name);
+    }
+    Ok(map)
+}
+
+/// The whole of `year` as a self-contained HTML table: one month grid per cell, weeks starting on Sunday.
+///
+/// Holiday cells get the CSS class `holiday` and the holiday name as `title`, weekends `sat`/`sun`.
+pub fn render_year_html(year: impl Into<Year>) -> Result<String> {
+    let year = year.into().to_u32()?;
+    let map = holiday_map(year)?;
+    let y = i32::try_from(year)?;
+    let mut html = format!("<table class=\"holiday-calendar\" data-year=\"{year}\">\n");
+    for row in 0..4 {
+        html.push_str("<tr>\n");
+        for month in row * 3 + 1..=row * 3 + 3 {
+            html.push_str("<td>\n");
+            render_month_html(&mut html, y, month, &map)?;
+            html.push_str("</td>\n");
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+    Ok(html)
+}
+
+// Appends the grid of one month to `html`.
+fn render_month_html(html: &mut String, year: i32, month: u32, map: &BTreeMap<NaiveDate, String>) -> Result<()> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1).ok_or(anyhow!("Invalid month: {year}-{month}"))?;
+    html.push_str(&format!("<table class=\"month\">\n<caption>{year}年{month}月</caption>\n<tr>"));
+    for label in ["日", "月", "火", "水", "木", "金", "土"] {
+        html.push_str(&format!("<th>{label}</th>"));
+    }
+    html.push_str("</tr>\n<tr>");
+    let offset = first.weekday().num_days_from_sunday();
+    for _ in 0..offset {
+        html.push_str("<td></td>");
+    }
+    let mut column = offset;
+    for date in first.iter_days().take_while(|d| d.month() == month) {
+        if column == 7 {
+            html.push_str("</tr>\n<tr>");
+            column = 0;
+        }
+        let mut classes = Vec::new();
+        match date.weekday() {
+            Weekday::Sun => classes.push("sun"),
+            Weekday::Sat => classes.push("sat"),
+            _ => {}
+        }
+        match map.get(&date) {
+            Some(name) => {
+                classes.push("holiday");
+                html.push_str(&format!("<td class=\"{}\" title=\"{}\">{}</td>", classes.join(" "), escape_html(name), date.day()));
+            }
+            None if classes.is_empty() => html.push_str(&format!("<td>{}</td>", date.day())),
+            None => html.push_str(&format!("<td class=\"{}\">{}</td>", classes.join(" "), date.day())),
+        }
+        column += 1;
+    }
+    for _ in column..7 {
+        html.push_str("<td></td>");
+    }
+    html.push_str("</tr>\n</table>\n");
+    Ok(())
+}
+
+// Escapes text for use in HTML content and attribute values.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Date of the holiday named `name` in `year`, or `None` if there is no such holiday that year.
+pub fn holiday_date_by_name(year: impl Into<Year>, name: &str) -> Result<Option<NaiveDate>> {
+    let year = year.into().to_u32()?;
+    Ok(holidays(year)?.into_iter().find(|h| h.name == name).map(|h| h.date))
+}
+
+/// How often the holiday named `name` falls on each weekday from `start` through `end` (both inclusive).
+///
+/// Years without that holiday are not counted; weekdays it never falls on are absent.
+pub fn weekday_distribution(name: &str, start: impl Into<Year>, end: impl Into<Year>) -> Result<HashMap<Weekday, u32>> {
+    let start = start.into().to_u32()?;
+    let end = end.into().to_u32()?;
+    let mut counts = HashMap::new();
+    for year in start..=end {
+        if let Some(date) = holiday_date_by_name(year, name)? {
+            *counts.entry(date.weekday()).or_insert(0) += 1;
+        }
+    }
+    Ok(counts)
+}
+
+/// Years from `start` through `end` (both inclusive) in which the holiday named `name` falls on Saturday or Sunday.
+///
+/// Years without that holiday are skipped.
+pub fn years_holiday_on_weekend(name: &str, start: impl Into<Year>, end: impl Into<Year>) -> Result<Vec<u32>> {
+    let start = start.into().to_u32()?;
+    let end = end.into().to_u32()?;
+    let mut years = Vec::new();
+    for year in start..=end {
+        if holiday_date_by_name(year, name)?.is_some_and(is_weekend) {
+            years.push(year);
+        }
+    }
+    Ok(years)
+}
+
+/// The year-end/new-year closure (年末年始) starting in `year`: Dec 29 through Jan 3 of the next year.
+///
+/// This is the administrative closure observed by government offices, not a statutory holiday,
+/// so these days are never part of [`holidays`] unless they are holidays in their own right.
+pub fn year_end_new_year_closure(year: impl Into<Year>) -> Result<(NaiveDate, NaiveDate)> {
+    let year = year.into().to_u32()?;
+    let start = i32::try_from(year).ok().and_then(|y| NaiveDate::from_ymd_opt(y, 12, 29));
+    let end = year.checked_add(1).and_then(|y| i32::try_from(y).ok()).and_then(|y| NaiveDate::from_ymd_opt(y, 1, 3));
+    match (start, end) {
+        (Some(start), Some(end)) => Ok((start, end)),
+        _ => Err(HolidayError::YearOutOfRange(year.into()).into()),
+    }
+}
+
+/// Whether offices are closed on `date`: a weekend or a holiday, and with `include_nenmatsu_nenshi` also
+/// Dec 29 through Jan 3, see [`year_end_new_year_closure`].
+///
+/// The year-end/new-year span is administrative convention, observed by government offices and banks,
+/// not a statutory holiday.
+pub fn is_closed(date: NaiveDate, include_nenmatsu_nenshi: bool) -> Result<bool> {
+    if include_nenmatsu_nenshi && matches!((date.month(), date.day()), (12, 29..=31) | (1, 1..=3)) {
+        return Ok(true);
+    }
+    Ok(is_weekend(date) || is_holiday(date)?)
+}
+
+/// Number of days off in `year`.
+///
+/// Without weekends this counts distinct holiday dates. With weekends it counts every Saturday,
+/// Sunday and holiday, counting a holiday on a weekend only once.
+pub fn total_days_off(year: impl Into<Year>, include_weekends: bool) -> Result<u32> {
+    let year = year.into().to_u32()?;
+    let dates: HashSet<NaiveDate> = holidays(year)?.into_iter().map(|h| h.date).collect();
+    if !include_weekends {
+        return Ok(dates.len() as u32);
+    }
+    let first = i32::try_from(year).ok()
+        .and_then(|y| NaiveDate::from_ymd_opt(y, 1, 1))
+        .ok_or_else(|| HolidayError::YearOutOfRange(year.into()))?;
+    let weekends = first.iter_days().take_while(|d| d.year() == first.year()).filter(|d| is_weekend(*d)).count();
+    let weekday_holidays = dates.iter().filter(|d| !is_weekend(**d)).count();
+    Ok((weekends + weekday_holidays) as u32)
+}
+
+/// Number of statutory holiday dates of `year` falling on a Saturday, which are lost to a Monday-to-Friday
+/// worker: unlike a holiday on Sunday, they get no substitute. 国民の休日 is not counted.
+pub fn days_off_lost_to_weekends(year: impl Into<Year>) -> Result<u32> {
+    let year = year.into().to_u32()?;
+    let dates: HashSet<NaiveDate> = holidays(year)?
+        .into_iter()
+        .filter(|h| !h.substitute && h.category != HolidayCategory::National && h.date.weekday() == Weekday::Sat)
+        .map(|h| h.date)
+        .collect();
+    Ok(dates.len() as u32)
+}
+
+/// Holidays of `year` as one flag per day, indexed by day of the year from 0 (`NaiveDate::ordinal0`).
+///
+/// The result has 366 entries in a leap year and 365 otherwise.
+pub fn holiday_bitmap(year: impl Into<Year>) -> Result<Vec<bool>> {
+    let year = year.into().to_u32()?;
+    let y = i32::try_from(year).map_err(|_| HolidayError::YearOutOfRange(year.into()))?;
+    let days = if NaiveDate::from_ymd_opt(y, 2, 29).is_some() { 366 } else { 365 };
+    let mut bitmap = vec![false; days];
+    for h in holidays(year)? {
+        bitmap[h.date.ordinal0() as usize] = true;
+    }
+    Ok(bitmap)
+}
+
+/// The dates of the holidays of `year`, sorted and without duplicates, for callers not needing the names.
+pub fn holiday_dates(year: impl Into<Year>) -> Result<Vec<NaiveDate>> {
+    let year = year.into().to_u32()?;
+    let mut dates: Vec<NaiveDate> = holidays(year)?.into_iter().map(|h| h.date).collect();
+    dates.dedup();
+    Ok(dates)
+}
+
+/// The longest run of consecutive days off within `year`: its first day, last day and length.
+///
+/// Weekends count as days off alongside every kind of holiday. Of equally long runs the earliest wins.
+pub fn longest_break(year: impl Into<Year>) -> Result<(NaiveDate, NaiveDate, u32)> {
+    let year = year.into().to_u32()?;
+    let mut best: Option<(NaiveDate, NaiveDate, u32)> = None;
+    for run in year_day_off_runs(year)? {
+        if best.is_none_or(|(_, _, len)| run.2 > len) {
+            best = Some(run);
+        }
+    }
+    best.ok_or_else(|| anyhow!("no day off in {}", year))
+}
+
+/// Every run of at least `min_length` consecutive days off in `year`, as its first day, last day and length.
+///
+/// Days off are counted as in [`longest_break`]; Golden Week and the 3連休 around Happy Mondays
+/// are found with a `min_length` of 3.
+pub fn long_weekends(year: impl Into<Year>, min_length: u32) -> Result<Vec<(NaiveDate, NaiveDate, u32)>> {
+    let year = year.into().to_u32()?;
+    let mut runs = year_day_off_runs(year)?;
+    runs.retain(|(_, _, len)| *len >= min_length);
+    Ok(runs)
+}
+
+// The runs of weekends and holidays within `year`, cut at New Year
+fn year_day_off_runs(year: u32) -> Result<Vec<(NaiveDate, NaiveDate, u32)>> {
+    let first = i32::try_from(year).ok()
+        .and_then(|y| NaiveDate::from_ymd_opt(y, 1, 1))
+        .ok_or_else(|| HolidayError::YearOutOfRange(year.into()))?;
+    let bitmap = holiday_bitmap(year)?;
+    let days = first.iter_days().zip(bitmap).map(|(date, holiday)| (date, holiday || is_weekend(date)));
+    Ok(day_off_runs(days))
+}
+
+/// The run of consecutive days off that `date` belongs to, as its first and last day,
+/// or `None` if `date` is a working day.
+///
+/// Weekends count as days off alongside every kind of holiday, as in [`longest_break`].
+/// The run may cross New Year. Near the ends of `Year::MIN..=Year::MAX` only weekends count beyond them,
+/// see [`holidays_between`].
+pub fn break_containing(date: NaiveDate) -> Result<Option<(NaiveDate, NaiveDate)>> {
+    // No run of days off comes close to a month
+    let window = Duration::days(31);
+    let (start, end) = match (date.checked_sub_signed(window), date.checked_add_signed(window)) {
+        (Some(start), Some(end)) => (start, end),
+        _ => return Err(HolidayError::YearOutOfRange(date.year().into()).into()),
+    };
+    let holidays: HashSet<NaiveDate> = holidays_between(start, end)?.into_iter().map(|h| h.date).collect();
+    let days = start.iter_days().take_while(|d| *d <= end).map(|d| (d, holidays.contains(&d) || is_weekend(d)));
+    Ok(day_off_runs(days)
+        .into_iter()
+        .find(|(first, last, _)| *first <= date && date <= *last)
+        .map(|(first, last, _)| (first, last)))
+}
+
+/// The rule placing the holiday `name` each year, or `None` if `name` is not a statutory holiday.
+///
+/// Relocations for special events, see the module notes, are not reflected.
+pub fn holiday_rule(name: &str) -> Result<Option<HolidayRule>> {
+    match name {
+        VERNAL_EQUINOX_NAME => return Ok(Some(HolidayRule::Equinox { month: 3 })),
+        AUTUMNAL_EQUINOX_NAME => return Ok(Some(HolidayRule::Equinox { month: 9 })),
+        _ => {}
+    }
+    // The latest rule, as in force today
+    let d = match schedule()?.iter().filter(|d| d.name == name).max_by_key(|d| d.since) {
+        Some(d) => d,
+        None => return Ok(None),
+    };
+    let rule = if d.relative {
+        let condition = d.condition.ok_or_else(|| anyhow!("{} is relative but has no condition", d.name))?;
+        HolidayRule::NthWeekday { month: condition.month, n: condition.nth, weekday: condition.weekday }
+    } else {
+        let date = d.date.as_deref().ok_or_else(|| anyhow!("{} has no date", d.name))?;
+        // A leap year accepts every month/day of the base data
+        let date = parse_month_day(2000, date).map_err(|e| anyhow!("invalid date {:?} for {}: {}", date, d.name, e))?;
+        HolidayRule::Fixed { month: date.month(), day: date.day() }
+    };
+    Ok(Some(rule))
+}
+
+/// The month the holiday `name` falls in under its current rule, e.g. 9 for 秋分の日, or `None` if
+/// `name` is not a statutory holiday. Year-independent, for grouping holidays by season.
+///
+/// The base data is embedded, so reading it only fails for a broken build; this returns `None` then.
+pub fn typical_month(name: &str) -> Option<u32> {
+    match holiday_rule(name).ok()?? {
+        HolidayRule::Fixed { month, .. } | HolidayRule::NthWeekday { month, .. } | HolidayRule::Equinox { month } => {
+            Some(month)
+        }
+    }
+}
+
+/// Holidays of `year` with `overrides` applied in order, e.g. to see where the substitutes fall if
+/// 海の日 moved, with substitutes and 国民の休日 found again afterwards. Items carry the default fields.
+///
+/// Overrides apply to `year` only, the previous December is left as is. An override naming no holiday
+/// of `year` is an error.
+pub fn holidays_with_overrides(year: impl Into<Year>, overrides: &[RuleOverride]) -> Result<Vec<HolidayShapedItem>> {
+    let year = year.into().to_u32()?;
+    let mut days = placed_holidays(year, year, EquinoxSource::default())?;
+    let in_year = |h: &Holiday| u32::try_from(h.date.year()) == Ok(year);
+    for o in overrides {
+        match o {
+            RuleOverride::Disable { name } => {
+                let before = days.len();
+                days.retain(|h| !(h.name == *name && in_year(h)));
+                if days.len() == before {
+                    return Err(anyhow!("{} is overridden but not a holiday of {}", name, year));
+                }
+            }
+            RuleOverride::Rule { name, rule } => {
+                let placed = place_by_rule(year, name, *rule)?;
+                let holiday = days.iter_mut()
+                    .find(|h| h.name == *name && in_year(h))
+                    .ok_or_else(|| anyhow!("{} is overridden but not a holiday of {}", name, year))?;
+                trace_step!(name = %name, from = %holiday.date, to = %placed.date, "holiday overridden");
+                *holiday = placed;
+            }
+        }
+    }
+    let builder = OutputBuilder::new(OutputFormat::JSON);
+    Ok(apply_within_year(year, days, &rules_with(SubstituteNaming::default()))
+        .into_iter()
+        .map(|h| builder.shape(h, &HashMap::new()))
+        .collect())
+}
+
+// The holiday `name` placed in `year` by `rule`
+fn place_by_rule(year: u32, name: &str, rule: HolidayRule) -> Result<Holiday> {
+    let (date, category, confidence) = match rule {
+        HolidayRule::Fixed { month, day } => {
+            let date = i32::try_from(year).ok().and_then(|y| NaiveDate::from_ymd_opt(y, month, day))
+                .ok_or_else(|| anyhow!("{}/{} does not exist in {}", month, day, year))?;
+            (date, HolidayCategory::Fixed, Confidence::Enacted)
+        }
+        HolidayRule::NthWeekday { month, n, weekday } => {
+            let date = nth_weekday_of_month(year, month, weekday, n)
+                .ok_or_else(|| anyhow!("{} does not resolve to a date in {}", rule, year))?;
+            (date, HolidayCategory::HappyMonday, Confidence::Enacted)
+        }
+        HolidayRule::Equinox { month } => {
+            let equinox = pick_exuinox_from_year(year, year, EquinoxSource::default())?
+                .into_iter()
+                .find(|h| h.date.month() == month)
+                .ok_or_else(|| anyhow!("no equinox holiday in month {} of {}", month, year))?;
+            (equinox.date, equinox.category, equinox.confidence)
+        }
+    };
+    Ok(Holiday { name: name.to_string(), date, substitute: false, category, source: None, confidence })
+}
+
+/// Holidays of `target_year` as the law stood in `law_year`, e.g. 2018 under the current law has
+/// 天皇誕生日 on February 23 instead of December 23.
+///
+/// Only the holidays in force in `law_year` are placed in `target_year`, and the substitute and
+/// national holiday rules of `law_year` apply. Relocations for special events of `target_year`
+/// apply when `law_year` is not earlier. Items carry the default fields.
+pub fn holidays_as_of(target_year: impl Into<Year>, law_year: impl Into<Year>) -> Result<Vec<HolidayShapedItem>> {
+    let target_year = target_year.into().to_u32()?;
+    let law_year = law_year.into().to_u32()?;
+    let law = i32::try_from(law_year).map_err(|_| HolidayError::YearOutOfRange(law_year.into()))?;
+    let builder = OutputBuilder::new(OutputFormat::JSON);
+    Ok(compute_holidays(target_year, law_year, EquinoxSource::default(), &rules_as_of(SubstituteNaming::default(), law))?
+        .into_iter()
+        .map(|h| builder.shape(h, &HashMap::new()))
+        .collect())
+}
+
+/// Names of the holidays added, removed or moved by the amendment effective in `amendment_year`, e.g.
+/// 山の日 for 2016, or both 体育の日 and スポーツの日 for the renaming in 2020. Empty for years without one.
+///
+/// Rules of the base data starting in `amendment_year` or ending the year before count, and so do the
+/// equinoxes in 1948/1949, 国民の休日 from 1986 and 2007, and 振替休日 from 1973 and 2007.
+pub fn holidays_affected_by(amendment_year: impl Into<Year>) -> Result<Vec<&'static str>> {
+    let year = amendment_year.into().to_u32()?;
+    let mut names: Vec<&'static str> = Vec::new();
+    for d in schedule()? {
+        let changed = d.since == Some(year) || (year > 0 && d.until == Some(year - 1));
+        if changed && !names.contains(&d.name.as_str()) {
+            names.push(d.name.as_str());
+        }
+    }
+    if year == VERNAL_EQUINOX_SINCE {
+        names.push(VERNAL_EQUINOX_NAME);
+    }
+    if year == AUTUMNAL_EQUINOX_SINCE {
+        names.push(AUTUMNAL_EQUINOX_NAME);
+    }
+    let year = i32::try_from(year)?;
+    // The 2007 amendment also made a sandwiched Sunday a 国民の休日
+    if year == NATIONAL_HOLIDAY_SINCE || year == SUBSTITUTE_ROLLING_SINCE {
+        names.push(NATIONAL_HOLIDAY_NAME);
+    }
+    if year == SUBSTITUTE_SINCE || year == SUBSTITUTE_ROLLING_SINCE {
+        names.push(SUBSTITUTE_HOLIDAY_NAME);
+    }
+    Ok(names)
+}
+
+/// Explanations of the holidays missing from `year` although observed the years before and after,
+/// e.g. 2019 has no 天皇誕生日 between the reigns of two emperors. Usually empty.
+///
+/// The holiday lists simply omit such holidays; these notes are for consumers iterating years.
+pub fn year_notes(year: impl Into<Year>) -> Result<Vec<String>> {
+    let year = year.into().to_u32()?;
+    let schedule = schedule()?;
+    let (previous, next) = match (year.checked_sub(1), year.checked_add(1)) {
+        (Some(previous), Some(next)) => (previous, next),
+        _ => return Ok(Vec::new()),
+    };
+    let mut notes = Vec::new();
+    for d in schedule.iter().filter(|d| d.until == Some(previous)) {
+        let observed = |y: u32| schedule.iter().any(|x| x.name == d.name && x.in_force(y));
+        if observed(year) || !observed(next) {
+            continue;
+        }
+        let date = |x: &BaseHolyday| x.date.clone().unwrap_or_else(|| "a relative date".to_string());
+        let successor = schedule.iter().find(|x| x.name == d.name && x.in_force(next)).map(date).unwrap_or_default();
+        notes.push(format!(
+            "{} has no {}: it was on {} until {} and is on {} from {}",
+            year, d.name, date(d), previous, successor, next
+        ));
+    }
+    Ok(notes)
+}
+
+/// Holidays of the 7-day week containing `reference`, where weeks begin on `week_start`.
+///
+/// The week can span a month or year boundary, e.g. Monday 2025-12-29 through Sunday 2026-01-04.
+/// Items carry the default fields.
+pub fn holidays_this_week(reference: NaiveDate, week_start: Weekday) -> Result<Vec<HolidayShapedItem>> {
+    let offset = (reference.weekday().num_days_from_monday() + 7 - week_start.num_days_from_monday()) % 7;
+    let start = reference - Duration::days(offset.into());
+    let builder = OutputBuilder::new(OutputFormat::JSON);
+    Ok(holidays_between(start, start + Duration::days(6))?
+        .into_iter()
+        .map(|h| builder.shape(h, &HashMap::new()))
+        .collect())
+}
+
+/// Holidays of `year` grouped into clusters of adjacent holidays, e.g. Golden Week, in date order.
+///
+/// Holidays on consecutive days share a cluster, and so do holidays separated only by a weekend,
+/// such as a Friday and the following Monday; the weekend days themselves are not listed.
+/// A holiday with no neighbour forms a cluster of its own. Items carry the default fields.
+pub fn holiday_clusters(year: impl Into<Year>) -> Result<Vec<Vec<HolidayShapedItem>>> {
+    let year = year.into().to_u32()?;
+    let builder = OutputBuilder::new(OutputFormat::JSON);
+    Ok(cluster_holidays(holidays(year)?)
+        .into_iter()
+        .map(|cluster| cluster.into_iter().map(|h| builder.shape(h, &HashMap::new())).collect())
+        .collect())
+}
+
+// Groups sorted holidays as described in `holiday_clusters`.
+fn cluster_holidays(days: Vec<Holiday>) -> Vec<Vec<Holiday>> {
+    let mut clusters: Vec<Vec<Holiday>> = Vec::new();
+    for h in days {
+        let joins = clusters.last().and_then(|c| c.last()).is_some_and(|last| {
+            last.date.iter_days().skip(1).take_while(|d| *d < h.date).all(is_weekend)
+        });
+        match clusters.last_mut() {
+            Some(cluster) if joins => cluster.push(h),
+            _ => clusters.push(vec![h]),
+        }
+    }
+    clusters
+}
+
+fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+// Position, from 1, and length of the run of days off, holidays and weekends, around each date of `days`.
+// Only `days` and weekends are looked at, so a run reaching into a neighbouring year counts that year's weekends only.
+fn day_off_positions(days: &[Holiday]) -> HashMap<NaiveDate, (u32, u32)> {
+    let (first, last) = match (days.first(), days.last()) {
+        (Some(first), Some(last)) => (first.date - Duration::days(7), last.date + Duration::days(7)),
+        _ => return HashMap::new(),
+    };
+    let dates: HashSet<NaiveDate> = days.iter().map(|h| h.date).collect();
+    let span = first.iter_days().take_while(|d| *d <= last).map(|d| (d, dates.contains(&d) || is_weekend(d)));
+    let mut positions: HashMap<NaiveDate, (u32, u32)> = HashMap::new();
+    for (start, end, len) in day_off_runs(span) {
+        for (position, date) in (1..).zip(start.iter_days().take_while(|d| *d <= end)) {
+            if dates.contains(&date) {
+                positions.insert(date, (position, len));
+            }
+        }
+    }
+    positions
+}
+
+// Runs of consecutive days flagged as days off, as (first day, last day, length), in order.
+fn day_off_runs(days: impl Iterator<Item = (NaiveDate, bool)>) -> Vec<(NaiveDate, NaiveDate, u32)> {
+    let mut runs: Vec<(NaiveDate, NaiveDate, u32)> = Vec::new();
+    let mut previous_off = false;
+    for (date, off) in days {
+        if off {
+            match runs.last_mut() {
+                Some(run) if previous_off => {
+                    run.1 = date;
+                    run.2 += 1;
+                }
+                _ => runs.push((date, date, 1)),
+            }
+        }
+        previous_off = off;
+    }
+    runs
+}
+
+/// Holidays of `year` that actually add a day off: those on Monday to Friday, including substitutes.
+///
+/// Holidays on Saturday are lost and holidays on Sunday are represented by their substitute,
+/// so neither is listed. Items carry the default fields.
+pub fn effective_days_off(year: impl Into<Year>) -> Result<Vec<HolidayShapedItem>> {
+    let year = year.into().to_u32()?;
+    let builder = OutputBuilder::new(OutputFormat::JSON);
+    Ok(holidays(year)?
+        .into_iter()
+        .filter(|h| !is_weekend(h.date))
+        .map(|h| builder.shape(h, &HashMap::new()))
+        .collect())
+}
+
+/// Each fixed-date holiday of `year` with the weekday it falls on, in date order.
+///
+/// Shows at a glance which fixed holidays are lost to a weekend. Substitutes are not listed.
+pub fn fixed_holiday_weekdays(year: impl Into<Year>) -> Result<Vec<(String, Weekday)>> {
+    let year = year.into().to_u32()?;
+    Ok(holidays(year)?
+        .into_iter()
+        .filter(|h| h.category == HolidayCategory::Fixed)
+        .map(|h| (h.name, h.date.weekday()))
+        .collect())
+}
+
+/// Substitute holidays of `year`, the days off a typical year does not have.
+///
+/// The typical year is taken from the 28 years around `year`, a full cycle of weekdays and leap years,
+/// within `Year::MIN..=Year::MAX`. A substitute is usual when one falls on the same month and day in at
+/// least a quarter of those years, like 5/6 after a ゴールデンウィーク holiday on Sunday since 2007, and is
+/// listed otherwise. A year without any returns an empty list. Items carry the default fields.
+pub fn unusual_substitutes(year: impl Into<Year>) -> Result<Vec<HolidayShapedItem>> {
+    let year = year.into().to_u32()?;
+    let window = year.saturating_sub(14).max(Year::MIN as u32)..=(year + 13).min(Year::MAX as u32);
+    let mut seen: HashMap<(u32, u32), u32> = HashMap::new();
+    for y in window.clone() {
+        // Substitutes of the window's years in January are counted under their own year
+        for h in holidays(y)?.into_iter().filter(|h| h.substitute && h.date.year() as u32 == y) {
+            *seen.entry((h.date.month(), h.date.day())).or_insert(0) += 1;
+        }
+    }
+    let years = window.count() as u32;
+    let builder = OutputBuilder::new(OutputFormat::JSON);
+    Ok(holidays(year)?
+        .into_iter()
+        .filter(|h| h.substitute && seen.get(&(h.date.month(), h.date.day())).copied().unwrap_or(0) * 4 < years)
+        .map(|h| builder.shape(h, &HashMap::new()))
+        .collect())
+}
+
+/// Whether `year` has Silver Week: 敬老の日, a 国民の休日 and 秋分の日 on consecutive days.
+///
+/// This happens when 敬老の日 falls on September 21 and 秋分の日 on September 23.
+pub fn has_silver_week(year: impl Into<Year>) -> Result<bool> {
+    let year = year.into().to_u32()?;
+    Ok(holidays(year)?.windows(3).any(|w| {
+        w[0].name == RESPECT_FOR_THE_AGED_DAY_NAME
+            && w[1].category == HolidayCategory::National
+            && w[2].name == AUTUMNAL_EQUINOX_NAME
+            && w[2].date - w[0].date == Duration::days(2)
+    }))
+}
+
+/// A one-sentence summary of `year` in Japanese, for command-line output, e.g.
+/// `2024年の祝日は21日間、うち振替休日5日、最長連休はゴールデンウィーク（5月3日〜5月6日の4日間）です。`
+///
+/// The longest break is found as in [`longest_break`] and labelled ゴールデンウィーク or シルバーウィーク
+/// when it covers 5月4日 or 国民の休日 in September.
+pub fn describe(year: impl Into<Year>) -> Result<String> {
+    let year = year.into().to_u32()?;
+    let days = holidays(year)?;
+    let dates: HashSet<NaiveDate> = days.iter().map(|h| h.date).collect();
+    let substitutes = days.iter().filter(|h| h.substitute).count();
+    let (first, last, len) = longest_break(year)?;
+    let covers = |h: &&Holiday| first <= h.date && h.date <= last;
+    let label = if days.iter().filter(covers).any(|h| (h.date.month(), h.date.day()) == (5, 4)) {
+        "ゴールデンウィーク"
+    } else if days.iter().filter(covers).any(|h| h.date.month() == 9 && h.category == HolidayCategory::National) {
+        "シルバーウィーク"
+    } else {
+        ""
+    };
+    let span = format!("{}月{}日〜{}月{}日の{}日間", first.month(), first.day(), last.month(), last.day(), len);
+    let longest = if label.is_empty() { span } else { format!("{}（{}）", label, span) };
+    Ok(format!("{}年の祝日は{}日間、うち振替休日{}日、最長連休は{}です。", year, dates.len(), substitutes, longest))
+}
+
+/// What the crate can answer, see `coverage`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage {
+    /// The years accepted by `Year::new`
+    pub min_year: i32,
+    pub max_year: i32,
+    /// Last year of officially announced equinox days; later ones are predicted
+    pub equinox_official_until: u32,
+    /// The substitute holiday rule of each era: first year, last year (open-ended when `None`) and mode.
+    /// The first year of `SingleMonday`, 1973, is partial, see `substitute_since`
+    pub substitute_rules_by_era: Vec<(i32, Option<i32>, SubstituteMode)>,
+    /// The first day a holiday on Sunday gets a substitute, 1973-04-12; earlier holidays of 1973 have none
+    pub substitute_since: NaiveDate,
+}
+
+/// The supported years and the provenance of the data, e.g. to limit a year picker or show which
+/// equinoxes are predicted. Built from `Year::MIN`/`Year::MAX`, `EQUINOX_OFFICIAL_UNTIL` and the
+/// `SUBSTITUTE_SINCE_DATE`/`SUBSTITUTE_ROLLING_SINCE` boundaries.
+pub fn coverage() -> Coverage {
+    Coverage {
+        min_year: Year::MIN,
+        max_year: Year::MAX,
+        equinox_official_until: EQUINOX_OFFICIAL_UNTIL,
+        substitute_rules_by_era: vec![
+            (Year::MIN, Some(SUBSTITUTE_SINCE - 1), substitute_rule_for_year(SUBSTITUTE_SINCE - 1)),
+            (SUBSTITUTE_SINCE, Some(SUBSTITUTE_ROLLING_SINCE - 1), substitute_rule_for_year(SUBSTITUTE_SINCE)),
+            (SUBSTITUTE_ROLLING_SINCE, None, substitute_rule_for_year(SUBSTITUTE_ROLLING_SINCE)),
+        ],
+        substitute_since: SUBSTITUTE_SINCE_DATE,
+    }
+}
+
+/// Runs consistency checks over the holidays of `year` and lists every violation found.
+///
+/// Checks that no date has two statutory holidays, that substitutes fall on weekdays right after
+/// a run of holidays starting on Sunday, that national holidays sit between two statutory ones, and
+/// that the year has one 春分の日 and one 秋分の日 once they exist. A year outside the equinox table,
+/// or a failure to compute the holidays, is reported as the only violation.
+pub fn validate_year(year: impl Into<Year>) -> Result<(), Vec<String>> {
+    let year = year.into().to_u32().map_err(|e| vec![e.to_string()])?;
+    if equinox_days(year).is_none() {
+        return Err(vec![format!("{}: outside the equinox table", year)]);
+    }
+    let days = holidays(year).map_err(|e| vec![e.to_string()])?;
+    let violations = check_holidays(year, &days);
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+// The checks of `validate_year` over any holiday list of `year`.
+fn check_holidays(year: u32, days: &[Holiday]) -> Vec<String> {
+    let mut violations: Vec<String> = Vec::new();
+    let statutory = |date: NaiveDate| days.iter().any(|h| h.date == date && !h.substitute);
+    let any_holiday = |date: NaiveDate| days.iter().any(|h| h.date == date);
+
+    let mut seen = HashSet::new();
+    for h in days.iter().filter(|h| !h.substitute) {
+        if !seen.insert(h.date) {
+            violations.push(format!("{}: more than one statutory holiday", h.date));
+        }
+    }
+    for h in days.iter().filter(|h| h.category == HolidayCategory::Substitute) {
+        if is_weekend(h.date) {
+            violations.push(format!("{}: substitute {} falls on a weekend", h.date, h.name));
+        }
+        // Walk back over the holidays the substitute was pushed past, to the Sunday it stands in for
+        let mut day = h.date - Duration::days(1);
+        while any_holiday(day) && day.weekday() != Weekday::Sun {
+            day -= Duration::days(1);
+        }
+        let source = days.iter().find(|s| s.date == day && !s.substitute);
+        if day.weekday() != Weekday::Sun || source.is_none() {
+            violations.push(format!("{}: substitute {} does not follow a holiday on Sunday", h.date, h.name));
+        } else if h.source.as_deref() != source.map(|s| s.name.as_str()) {
+            violations.push(format!("{}: substitute {} names {:?} as its source", h.date, h.name, h.source));
+        }
+    }
+    for h in days.iter().filter(|h| h.category == HolidayCategory::National) {
+        if !statutory(h.date - Duration::days(1)) || !statutory(h.date + Duration::days(1)) {
+            violations.push(format!("{}: national holiday is not between two holidays", h.date));
+        }
+    }
+    for (name, month, since) in [(VERNAL_EQUINOX_NAME, 3, VERNAL_EQUINOX_SINCE), (AUTUMNAL_EQUINOX_NAME, 9, AUTUMNAL_EQUINOX_SINCE)] {
+        let count = days.iter().filter(|h| h.category == HolidayCategory::Equinox && h.date.month() == month).count();
+        if year >= since && count != 1 {
+            violations.push(format!("{}: {} {} found, expected one", year, count, name));
+        }
+    }
+    violations
+}
+
+/// Whether `date` is a holiday (including substitute holidays).
+///
+/// Fails with `HolidayError::YearOutOfRange` for a date outside `Year::MIN..=Year::MAX`.
+pub fn is_holiday(date: NaiveDate) -> Result<bool> {
+    let year = Year::from(date.year()).to_u32()?;
+    Ok(holidays(year)?.iter().any(|h| h.date == date))
+}
+
+/// The holiday name on each of `dates`, or `None` on a regular day, in the order given.
+///
+/// The holidays of each year are computed once, so this suits classifying many dates at a time.
+/// A date outside `Year::MIN..=Year::MAX` fails the batch with `HolidayError::YearOutOfRange`, as in [`is_holiday`].
+pub fn holidays_for_date_list(dates: &[NaiveDate]) -> Result<Vec<Option<String>>> {
+    let mut sets: HashMap<i32, HolidaySet> = HashMap::new();
+    let mut names = Vec::with_capacity(dates.len());
+    for date in dates {
+        let set = match sets.entry(date.year()) {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(HolidaySet::for_year(date.year())?),
+        };
+        names.push(set.name(*date).map(str::to_string));
+    }
+    Ok(names)
+}
+
+/// Holiday status of a `%Y-%m-%d` date string: the holiday name, or `None` on a regular day.
+pub fn holiday_status(date_str: &str) -> Result<Option<String>, HolidayError> {
+    let date = NaiveDate::parse_from_str(date_str.trim(), "%Y-%m-%d")
+        .map_err(|_| HolidayError::InvalidDate(date_str.to_string()))?;
+    Ok(holiday_map(date.year())?.remove(&date))
+}
+
+/// The name of the holiday that `date` substitutes for, if `date` is a 振替休日.
+pub fn substitute_source(date: NaiveDate) -> Result<Option<String>> {
+    JapanNationalCalendar::default().substitute_source(date)
+}
+
+/// Whether today is a holiday, and its name.
+///
+/// "Today" is the current wall-clock date in `tz`, so the answer flips at midnight in `tz`,
+/// not at midnight JST or UTC. Pass a JST offset to follow the Japanese calendar day wherever
+/// the caller runs.
+pub fn today_status<Tz: TimeZone>(tz: &Tz) -> Result<(bool, Option<String>)> {
+    date_status(Utc::now().with_timezone(tz).date_naive())
+}
+
+/// The holiday closest to `from`, past or future: its date, name and signed distance in days.
+///
+/// The distance is negative for a past holiday and 0 when `from` is a holiday. Equally distant
+/// holidays resolve to the upcoming one. Adjacent years are searched, so this works across New Year.
+pub fn nearest_holiday(from: NaiveDate) -> Result<(NaiveDate, String, i64)> {
+    let year = Year::from(from.year()).to_u32()?;
+    let mut candidates = BTreeMap::new();
+    // Adjacent years beyond `Year::MIN..=Year::MAX` have no holidays to offer
+    for y in (year - 1..=year + 1).filter(|y| Year::SUPPORTED.contains(y)) {
+        candidates.extend(holiday_map(y)?);
+    }
+    candidates
+        .into_iter()
+        .map(|(date, name)| (date, name, (date - from).num_days()))
+        .min_by_key(|(_, _, distance)| (distance.abs(), *distance < 0))
+        .ok_or_else(|| anyhow!("no holiday around {}", from))
+}
+
+/// Days from `from` until the next 海の日 or other holiday named `name`, 0 when `from` is that holiday,
+/// for countdowns like あと○日で海の日.
+///
+/// The year of `from` and the next one are searched; `None` if the holiday falls in neither, e.g. 天皇誕生日
+/// from 2018-12-24.
+pub fn days_until(name: &str, from: NaiveDate) -> Result<Option<i64>> {
+    let year = Year::from(from.year()).to_u32()?;
+    for y in [year, year + 1].into_iter().filter(|y| Year::SUPPORTED.contains(y)) {
+        if let Some(date) = holiday_date_by_name(y, name)?.filter(|date| *date >= from) {
+            return Ok(Some((date - from).num_days()));
+        }
+    }
+    Ok(None)
+}
+
+/// The last 海の日 or other holiday named `name` before `before`, e.g. to count the days since it.
+///
+/// The year of `before` and the previous one are searched; `None` if the holiday falls in neither, e.g.
+/// 天皇誕生日 before 2020-01-01.
+pub fn previous_occurrence(name: &str, before: NaiveDate) -> Result<Option<NaiveDate>> {
+    let year = Year::from(before.year()).to_u32()?;
+    for y in [year, year - 1].into_iter().filter(|y| Year::SUPPORTED.contains(y)) {
+        if let Some(date) = holiday_date_by_name(y, name)?.filter(|date| *date < before) {
+            return Ok(Some(date));
+        }
+    }
+    Ok(None)
+}
+
+/// Holidays of `year` as Unix timestamps (seconds) with their names.
+///
+/// Each timestamp is 00:00 JST (UTC+9) of the holiday, the instant `datetime_rfc3339` shows,
+/// so a holiday starts at 15:00 UTC of the previous day. Use [`holidays_as_datetimes`] for other timezones.
+pub fn holidays_epoch(year: impl Into<Year>) -> Result<Vec<(i64, String)>> {
+    let year = year.into().to_u32()?;
+    Ok(holidays(year)?
+        .into_iter()
+        .map(|h| (jst_midnight(h.date).timestamp(), h.name))
+        .collect())
+}
+
+/// Holidays of `year` as midnight in `tz`, with their names.
+///
+/// Where a daylight saving change skips midnight, the first valid instant of that day is used.
+/// With the `chrono-tz` feature, `jpn_holiday_atlas::chrono_tz::Tz` names IANA timezones such as `Asia/Tokyo`.
+pub fn holidays_as_datetimes<Tz: TimeZone>(year: impl Into<Year>, tz: &Tz) -> Result<Vec<(DateTime<Tz>, String)>> {
+    let year = year.into().to_u32()?;
+    holidays(year)?
+        .into_iter()
+        .map(|h| {
+            let midnight = h.date.and_time(NaiveTime::MIN);
+            let start = (0..24)
+                .find_map(|hour| tz.from_local_datetime(&(midnight + Duration::hours(hour))).earliest())
+                .ok_or_else(|| anyhow!("{} has no valid time in the timezone", h.date))?;
+            Ok((start, h.name))
+        })
+        .collect()
+}
+
+fn date_status(date: NaiveDate) -> Result<(bool, Option<String>)> {
+    let year = match u32::try_from(date.year()) {
+        Ok(v) => v,
+        Err(_) => return Ok((false, None)),
+    };
+    let name = HolidaySet::for_year(year)?.name(date).map(str::to_string);
+    Ok((name.is_some(), name))
+}
+
+/// Whether the epoch timestamp `ts` (seconds) falls on a holiday.
+///
+/// The instant is mapped to a calendar day in Japan Standard Time (UTC+09:00),
+/// so e.g. `2023-12-31T15:00:00Z` is treated as 2024-01-01.
+pub fn is_holiday_timestamp(ts: i64) -> Result<bool> {
+    let instant = DateTime::from_timestamp(ts, 0)
+        .ok_or_else(|| anyhow!("timestamp {} is out of range", ts))?;
+    is_holiday(instant.with_timezone(&jst()).date_naive())
+}
+
+// private functions
+
+fn jst() -> FixedOffset {
+    FixedOffset::east_opt(JST_OFFSET_SECS).unwrap()
+}
+
+// 00:00 of `date` in Japan Standard Time
+fn jst_midnight(date: NaiveDate) -> DateTime<FixedOffset> {
+    date.and_hms_opt(0, 0, 0).unwrap().and_local_timezone(jst()).unwrap()
+}
+
+fn pick_exuinox_from_year(year:u32, law_year: u32, source: EquinoxSource) -> Result<Vec<Holiday>> {
+    let (spring, fall) = match source.days(year) {
+        Some(v) => v,
+        None => return Ok(Vec::new()),
+    };
+    let mut return_value: Vec<Holiday> = Vec::new();
+    let equinoxes = [(VERNAL_EQUINOX_NAME, 3, spring, VERNAL_EQUINOX_SINCE), (AUTUMNAL_EQUINOX_NAME, 9, fall, AUTUMNAL_EQUINOX_SINCE)];
+    for (name, month, day, _) in equinoxes.into_iter().filter(|e| e.3 <= law_year) {
+        let date = NaiveDate::from_ymd_opt(year as i32, month, day)
+            .ok_or_else(|| anyhow!("invalid {} date {}/{} for {}", name, month, day, year))?;
+        return_value.push(Holiday {
+            name: name.to_string(),
+            date,
+            substitute: false,
+            category: HolidayCategory::Equinox,
+            source: None,
+            confidence: Confidence::for_equinox(year),
+        });
+    }
+    Ok(return_value)
+}
+
+// for base dates
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(schedule)))]
+fn prepare_holidays<'a>(year: u32, schedule: impl IntoIterator<Item = &'a BaseHolyday>)->Result<Vec<Holiday>> {
+    let mut days: Vec<Holiday> = Vec::new();
+    for d in schedule {
+        let holiday = if d.relative {
+            let condition = d.condition.ok_or_else(|| anyhow!("{} is relative but has no condition", d.name))?;
+            let relative_date = nth_weekday_of_month(year, condition.month, condition.weekday, condition.nth)
+                .ok_or_else(|| anyhow!("{} does not resolve to a date in {}", d.name, year))?;
+            Holiday {
+                name: d.name.clone(),
+                date: relative_date,
+                substitute: false,
+                category: HolidayCategory::HappyMonday,
+                source: None,
+                confidence: Confidence::Enacted,
+            }
+        } else {
+            let date = d.date.as_deref().ok_or_else(|| anyhow!("{} has no date", d.name))?;
+            let date = parse_month_day(year, date)
+                .map_err(|e| anyhow!("invalid date {:?} for {} in {}: {}", date, d.name, year, e))?;
+            Holiday {
+                name: d.name.clone(),
+                date,
+                substitute: false,
+                category: HolidayCategory::Fixed,
+                source: None,
+                confidence: Confidence::Enacted,
+            }
+        };
+        trace_step!(name = %holiday.name, date = %holiday.date, "holiday added");
+        days.push(holiday);
+    }
+    Ok(days)
+}
+
+// `M/D` of the base data in `year`, without a round-trip through a formatted date string.
+// Leading zeros are accepted (`01/01`); anything but exactly two numeric components is an error.
+fn parse_month_day(year: u32, date: &str) -> Result<NaiveDate> {
+    let parts: Vec<&str> = date.split('/').map(str::trim).collect();
+    let (month, day) = match parts.as_slice() {
+        [month, day] => (*month, *day),
+        _ => return Err(anyhow!("expected month/day, e.g. 1/1, but got {} components", parts.len())),
+    };
+    let number = |label: &str, value: &str| -> Result<u32> {
+        match value.parse() {
+            Ok(v) if value.bytes().all(|b| b.is_ascii_digit()) => Ok(v),
+            _ => Err(anyhow!("{} {:?} is not a number", label, value)),
+        }
+    };
+    let (month, day) = (number("month", month)?, number("day", day)?);
+    i32::try_from(year).ok()
+        .and_then(|y| NaiveDate::from_ymd_opt(y, month, day))
+        .ok_or_else(|| anyhow!("{}/{} does not exist in {}", month, day, year))
+}
+
+// for relative date, the nth weekday of the month.
+// Computed from the weekday of the 1st, so no days are walked; `None` if the month has no nth such weekday.
+// Works on calendar dates only, so the result does not depend on the local timezone.
+fn nth_weekday_of_month(year: u32, month: u32, weekday: Weekday, n: u32)-> Option<NaiveDate> {
+    let first = NaiveDate::from_ymd_opt(i32::try_from(year).ok()?, month, 1)?;
+    let offset = (weekday.num_days_from_monday() + 7 - first.weekday().num_days_from_monday()) % 7;
+    let day = n.checked_sub(1)?.checked_mul(7)?.checked_add(1 + offset)?;
+    first.with_day(day)
+}
+
+#[cfg(test)]
+pub mod test {
+    use pretty_assertions::assert_eq;
+    use chrono::NaiveDate;
+    #[test]
+    pub fn test_holiday_output_yml() {
+        let year = 2024;
+        let expected = "- name: 元旦\n  date: 2024-01-01\n  substitute: false\n- name: 成人の日\n  date: 2024-01-08\n  substitute: false\n- name: 建国記念の日\n  date: 2024-02-11\n  substitute: false\n- name: 振替休日(建国記念の日)\n  date: 2024-02-12\n  substitute: true\n- name: 天皇誕生日\n  date: 2024-02-23\n  substitute: false\n- name: 春分の日\n  date: 2024-03-20\n  substitute: false\n- name: 昭和の日\n  date: 2024-04-29\n  substitute: false\n- name: 憲法記念日\n  date: 2024-05-03\n  substitute: false\n- name: みどりの日\n  date: 2024-05-04\n  substitute: false\n- name: こどもの日\n  date: 2024-05-05\n  substitute: false\n- name: 振替休日(こどもの日)\n  date: 2024-05-06\n  substitute: true\n- name: 海の日\n  date: 2024-07-15\n  substitute: false\n- name: 山の日\n  date: 2024-08-11\n  substitute: false\n- name: 振替休日(山の日)\n  date: 2024-08-12\n  substitute: true\n- name: 敬老の日\n  date: 2024-09-16\n  substitute: false\n- name: 秋分の日\n  date: 2024-09-22\n  substitute: false\n- name: 振替休日(秋分の日)\n  date: 2024-09-23\n  substitute: true\n- name: スポーツの日\n  date: 2024-10-14\n  substitute: false\n- name: 文化の日\n  date: 2024-11-03\n  substitute: false\n- name: 振替休日(文化の日)\n  date: 2024-11-04\n  substitute: true\n- name: 勤労感謝の日\n  date: 2024-11-23\n  substitute: false\n";
+        let format = super::OutputFormat::YAML;
+        let result = super::holiday(format, year).unwrap();
+        assert_eq!(result, expected);
+    }
+    #[test]
+    pub fn test_holiday_output_json() {
+        let year = 2024;
+        let expected = "[\n  {\n    \"name\": \"元旦\",\n    \"date\": \"2024-01-01\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"成人の日\",\n    \"date\": \"2024-01-08\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"建国記念の日\",\n    \"date\": \"2024-02-11\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"振替休日(建国記念の日)\",\n    \"date\": \"2024-02-12\",\n    \"substitute\": true\n  },\n  {\n    \"name\": \"天皇誕生日\",\n    \"date\": \"2024-02-23\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"春分の日\",\n    \"date\": \"2024-03-20\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"昭和の日\",\n    \"date\": \"2024-04-29\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"憲法記念日\",\n    \"date\": \"2024-05-03\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"みどりの日\",\n    \"date\": \"2024-05-04\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"こどもの日\",\n    \"date\": \"2024-05-05\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"振替休日(こどもの日)\",\n    \"date\": \"2024-05-06\",\n    \"substitute\": true\n  },\n  {\n    \"name\": \"海の日\",\n    \"date\": \"2024-07-15\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"山の日\",\n    \"date\": \"2024-08-11\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"振替休日(山の日)\",\n    \"date\": \"2024-08-12\",\n    \"substitute\": true\n  },\n  {\n    \"name\": \"敬老の日\",\n    \"date\": \"2024-09-16\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"秋分の日\",\n    \"date\": \"2024-09-22\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"振替休日(秋分の日)\",\n    \"date\": \"2024-09-23\",\n    \"substitute\": true\n  },\n  {\n    \"name\": \"スポーツの日\",\n    \"date\": \"2024-10-14\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"文化の日\",\n    \"date\": \"2024-11-03\",\n    \"substitute\": false\n  },\n  {\n    \"name\": \"振替休日(文化の日)\",\n    \"date\": \"2024-11-04\",\n    \"substitute\": true\n  },\n  {\n    \"name\": \"勤労感謝の日\",\n    \"date\": \"2024-11-23\",\n    \"substitute\": false\n  }\n]";
+        let format = super::OutputFormat::JSON;
+        let result = super::holiday(format, year).unwrap();
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    pub fn test_holiday_output_csv() {
+        let year = 2024;
+        let expected = "name,date,substitute\n元旦,2024-01-01,false\n成人の日,2024-01-08,false\n建国記念の日,2024-02-11,false\n振替休日(建国記念の日),2024-02-12,true\n天皇誕生日,2024-02-23,false\n春分の日,2024-03-20,false\n昭和の日,2024-04-29,false\n憲法記念日,2024-05-03,false\nみどりの日,2024-05-04,false\nこどもの日,2024-05-05,false\n振替休日(こどもの日),2024-05-06,true\n海の日,2024-07-15,false\n山の日,2024-08-11,false\n振替休日(山の日),2024-08-12,true\n敬老の日,2024-09-16,false\n秋分の日,2024-09-22,false\n振替休日(秋分の日),2024-09-23,true\nスポーツの日,2024-10-14,false\n文化の日,2024-11-03,false\n振替休日(文化の日),2024-11-04,true\n勤労感謝の日,2024-11-23,false\n";
+        let format = super::OutputFormat::CSV;
+        let result = super::holiday(format, year).unwrap();
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    pub fn test_holiday_output_month_day() {
+        use super::{Fields, OutputBuilder, OutputFormat};
+        let json = OutputBuilder::new(OutputFormat::JSON).with_month_day(true).build(2024).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value[0], serde_json::json!({"name": "元旦", "date": "2024-01-01", "substitute": false, "month": 1, "day": 1}));
+        let csv = OutputBuilder::new(OutputFormat::CSV).with_month_day(true).build(2024).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("name,date,substitute,month,day"));
+        assert_eq!(lines.next(), Some("元旦,2024-01-01,false,1,1"));
+        assert!(csv.contains("\n振替休日(こどもの日),2024-05-06,true,5,6\n"), "{}", csv);
+
+        let yaml = OutputBuilder::new(OutputFormat::YAML).fields(Fields::MONTH | Fields::DAY).build(2024).unwrap();
+        assert!(yaml.starts_with("- name: 元旦\n  date: 2024-01-01\n  substitute: false\n  month: 1\n  day: 1\n"), "{}", yaml);
+        let items = OutputBuilder::new(OutputFormat::JSON).with_month_day(true).with_month_day(false).build_items(2024).unwrap();
+        assert!(items.iter().all(|i| i.month.is_none() && i.day.is_none()));
+    }
+
+    #[test]
+    pub fn test_holiday_output_ndjson() {
+        let result = super::holiday(super::OutputFormat::NDJSON, 2024).unwrap();
+        assert!(result.ends_with('\n'));
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines.len(), 21);
+        assert_eq!(lines[0], "{\"name\":\"元旦\",\"date\":\"2024-01-01\",\"substitute\":false}");
+        for line in lines {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert!(value.is_object());
+        }
+    }
+
+    #[test]
+    pub fn test_holidays_json_bytes() {
+        let bytes = super::holidays_json_bytes(2024).unwrap();
+        let json = super::holiday(super::OutputFormat::JSON, 2024).unwrap();
+        assert_eq!(bytes, json.into_bytes());
+    }
+
+    #[test]
+    pub fn test_holiday_output_rfc3339() {
+        let builder = super::OutputBuilder::new(super::OutputFormat::JSON).with_rfc3339(true);
+        let result = builder.build(2024).unwrap();
+        assert!(result.contains("\"datetime_rfc3339\": \"2024-01-01T00:00:00+09:00\""), "{}", result);
+
+        let csv = super::OutputBuilder::new(super::OutputFormat::CSV).with_rfc3339(true).build(2024).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("name,date,substitute,datetime_rfc3339"));
+        assert_eq!(lines.next(), Some("元旦,2024-01-01,false,2024-01-01T00:00:00+09:00"));
+
+        let plain = super::OutputBuilder::new(super::OutputFormat::JSON).build(2024).unwrap();
+        assert!(!plain.contains("datetime_rfc3339"));
+    }
+
+    #[test]
+    pub fn test_holiday_output_fields() {
+        use super::Fields;
+        let none = super::OutputBuilder::new(super::OutputFormat::NDJSON).fields(Fields::NONE).build(2024).unwrap();
+        assert_eq!(none.lines().next(), Some("{\"name\":\"元旦\",\"date\":\"2024-01-01\",\"substitute\":false}"));
+
+        let builder = super::OutputBuilder::new(super::OutputFormat::NDJSON).fields(Fields::DAY | Fields::CATEGORY);
+        let result = builder.build(2024).unwrap();
+        assert_eq!(result.lines().nth(1), Some("{\"name\":\"成人の日\",\"date\":\"2024-01-08\",\"substitute\":false,\"day\":8,\"category\":\"happy_monday\"}"));
+
+        let csv = super::OutputBuilder::new(super::OutputFormat::CSV).fields(Fields::ALL).build(2024).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("name,date,substitute,month,day,datetime_rfc3339,category,law_reference,confidence,long_weekend_length,cluster_index,name_en,era,time"));
+        assert_eq!(lines.next(), Some("元旦,2024-01-01,false,1,1,2024-01-01T00:00:00+09:00,fixed,国民の祝日に関する法律 第2条,enacted,3,3/3,New Year's Day,令和6年,1704034800"));
+
+        let without_rfc = super::OutputBuilder::new(super::OutputFormat::CSV).fields(Fields::ALL).with_rfc3339(false).build(2024).unwrap();
+        assert_eq!(without_rfc.lines().next(), Some("name,date,substitute,month,day,category,law_reference,confidence,long_weekend_length,cluster_index,name_en,era,time"));
+        let long_weekends = super::OutputBuilder::new(super::OutputFormat::CSV).fields(Fields::LONG_WEEKEND).build(2024).unwrap();
+        let rows: Vec<&str> = long_weekends.lines().collect();
+        // 成人の日 on Monday makes a 3連休 with the weekend before it
+        assert_eq!(rows[2], "成人の日,2024-01-08,false,3");
+        // Friday 2024-02-23 with the weekend after it
+        assert_eq!(rows[5], "天皇誕生日,2024-02-23,false,3");
+        // Golden Week runs 2024-05-03 through 2024-05-06
+        assert_eq!(rows[8], "憲法記念日,2024-05-03,false,4");
+        // 勤労感謝の日 on Saturday only makes a regular weekend
+        assert_eq!(rows[21], "勤労感謝の日,2024-11-23,false,");
+    }
+
+    #[test]
+    pub fn test_holiday_output_substitute_naming() {
+        let csv = super::OutputBuilder::new(super::OutputFormat::CSV)
+            .substitute_naming(super::SubstituteNaming::Custom(|source| format!("{} (observed)", source)))
+            .build(2024)
+            .unwrap();
+        assert!(csv.contains("\nこどもの日 (observed),2024-05-06,true\n"), "{}", csv);
+        let plain = super::OutputBuilder::new(super::OutputFormat::CSV)
+            .substitute_naming(super::SubstituteNaming::Plain)
+            .build(2024)
+            .unwrap();
+        assert!(plain.contains("\n振替休日,2024-05-06,true\n"), "{}", plain);
+    }
+
+    #[test]
+    pub fn test_holidays_between() {
+        let start = NaiveDate::from_ymd_opt(2024, 12, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+        let names: Vec<String> = super::holidays_between(start, end).unwrap().into_iter().map(|h| h.name).collect();
+        assert_eq!(names, vec!["元旦", "成人の日"]);
+        // Years outside the supported range contribute nothing
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let names: Vec<String> = super::holidays_between(date(1947, 12, 1), date(1948, 12, 1)).unwrap().into_iter().map(|h| h.name).collect();
+        assert_eq!(names, vec!["秋分の日", "文化の日", "勤労感謝の日"]);
+        assert_eq!(super::holidays_between(date(2099, 12, 1), date(2100, 12, 31)).unwrap().len(), 0);
+        assert_eq!(super::holidays_between(date(1900, 1, 1), date(1900, 12, 31)).unwrap().len(), 0);
+    }
+
+    #[test]
+    pub fn test_contains_holiday() {
+        let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+        assert!(super::contains_holiday(date(5, 1), date(5, 7)).unwrap());
+        assert!(super::contains_holiday(date(5, 6), date(5, 6)).unwrap());
+        assert!(!super::contains_holiday(date(6, 1), date(6, 30)).unwrap());
+        assert!(!super::contains_holiday(date(5, 7), date(5, 1)).unwrap());
+        assert!(super::contains_holiday(date(12, 30), NaiveDate::from_ymd_opt(2025, 1, 2).unwrap()).unwrap());
+        assert!(super::contains_holiday(NaiveDate::from_ymd_opt(1947, 1, 1).unwrap(), NaiveDate::from_ymd_opt(1948, 9, 23).unwrap()).unwrap());
+        assert!(!super::contains_holiday(NaiveDate::from_ymd_opt(2100, 1, 1).unwrap(), NaiveDate::from_ymd_opt(2100, 12, 31).unwrap()).unwrap());
+    }
+
+    #[test]
+    pub fn test_fiscal_year_holidays() {
+        let result = super::fiscal_year_holidays(2024).unwrap();
+        assert_eq!(result.first().unwrap().name, "昭和の日");
+        assert_eq!(result.first().unwrap().date, NaiveDate::from_ymd_opt(2024, 4, 29).unwrap());
+        // FY2024 ends with 春分の日 2025
+        assert_eq!(result.last().unwrap().name, "春分の日");
+        assert_eq!(result.last().unwrap().date, NaiveDate::from_ymd_opt(2025, 3, 20).unwrap());
+        assert!(result.windows(2).all(|w| w[0].date <= w[1].date));
+        // FY2099 stops with the supported years
+        assert_eq!(super::fiscal_year_holidays(2099).unwrap().last().unwrap().name, "勤労感謝の日");
+    }
+
+    #[test]
+    pub fn test_movable_holidays() {
+        let names: Vec<String> = super::movable_holidays(2024).unwrap().into_iter().map(|h| h.name).collect();
+        assert_eq!(names, vec!["成人の日", "春分の日", "海の日", "敬老の日", "秋分の日", "スポーツの日"]);
+    }
+
+    #[test]
+    pub fn test_equinoxes_and_fixed_holidays_interplay() {
+        let mut sandwiched = Vec::new();
+        for year in 2020..=2050 {
+            let days = super::holidays(year).unwrap();
+            let fixed: Vec<NaiveDate> = days.iter()
+                .filter(|h| h.category == super::HolidayCategory::Fixed)
+                .map(|h| h.date)
+                .collect();
+            let equinoxes: Vec<&super::Holiday> = days.iter()
+                .filter(|h| h.category == super::HolidayCategory::Equinox)
+                .collect();
+            assert_eq!(equinoxes.len(), 2, "{}", year);
+            for e in &equinoxes {
+                assert!(!fixed.contains(&e.date), "{} collides with a fixed holiday in {}", e.name, year);
+            }
+            let keiro = days.iter().find(|h| h.name == "敬老の日").unwrap();
+            let shubun = equinoxes[1];
+            if shubun.date - keiro.date == chrono::Duration::days(2) {
+                let between = keiro.date + chrono::Duration::days(1);
+                let entry = days.iter().find(|h| h.date == between);
+                assert_eq!(entry.map(|h| h.name.as_str()), Some("国民の休日"), "{}", year);
+                sandwiched.push(year);
+            }
+        }
+        assert_eq!(sandwiched, vec![2026, 2032, 2037, 2043, 2049]);
+    }
+
+    #[test]
+    pub fn test_holidays_grouped_by_month() {
+        let months = super::holidays_grouped_by_month(2024).unwrap();
+        // June and December have no holidays
+        assert_eq!(months.keys().copied().collect::<Vec<u32>>(), vec![1, 2, 3, 4, 5, 7, 8, 9, 10, 11]);
+        assert_eq!(months[&5].len(), 4);
+        assert_eq!(months.values().map(|v| v.len()).sum::<usize>(), 21);
+    }
+
+    #[test]
+    pub fn test_all_holiday_names() {
+        let names = super::all_holiday_names();
+        // 体育の日 was renamed スポーツの日 in 2020; 即位礼正殿の儀 was held in 1990 and 2019 but is listed once
+        assert_eq!(names.len(), 24);
+        for name in ["元旦", "即位の日", "昭和天皇の大喪の礼", "体育の日", "スポーツの日", "春分の日", "秋分の日", "国民の休日", "振替休日"] {
+            assert!(names.contains(&name), "{} is missing", name);
+        }
+    }
+
+    #[test]
+    pub fn test_holiday_map() {
+        let map = super::holiday_map(2024).unwrap();
+        assert_eq!(map.len(), 21);
+        let may: Vec<&str> = map
+            .range(NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()..NaiveDate::from_ymd_opt(2024, 6, 1).unwrap())
+            .map(|(_, name)| name.as_str())
+            .collect();
+        assert_eq!(may, vec!["憲法記念日", "みどりの日", "こどもの日", "振替休日(こどもの日)"]);
+    }
+
+    #[test]
+    pub fn test_year_end_new_year_closure() {
+        let (start, end) = super::year_end_new_year_closure(2024).unwrap();
+        assert_eq!(start, NaiveDate::from_ymd_opt(2024, 12, 29).unwrap());
+        assert_eq!(end, NaiveDate::from_ymd_opt(2025, 1, 3).unwrap());
+    }
+
+    #[test]
+    pub fn test_holiday_status() {
+        assert_eq!(super::holiday_status("2024-01-01").unwrap(), Some("元旦".to_string()));
+        assert_eq!(super::holiday_status("2024-05-06").unwrap(), Some("振替休日(こどもの日)".to_string()));
+        assert_eq!(super::holiday_status("2024-01-02").unwrap(), None);
+        assert!(matches!(super::holiday_status("2024-02-30"), Err(super::HolidayError::InvalidDate(_))));
+        assert!(matches!(super::holiday_status("01/01/2024"), Err(super::HolidayError::InvalidDate(_))));
+        assert!(matches!(super::holiday_status("1900-01-01"), Err(super::HolidayError::YearOutOfRange(1900))));
+        assert!(matches!(super::holiday_status("2100-01-01"), Err(super::HolidayError::YearOutOfRange(2100))));
+    }
+
+    #[test]
+    pub fn test_substitute_source() {
+        // 2023-01-01 is a Sunday
+        assert_eq!(super::substitute_source(NaiveDate::from_ymd_opt(2023, 1, 2).unwrap()).unwrap(), Some("元旦".to_string()));
+        assert_eq!(super::substitute_source(NaiveDate::from_ymd_opt(2023, 1, 1).unwrap()).unwrap(), None);
+        assert_eq!(super::substitute_source(NaiveDate::from_ymd_opt(2023, 1, 3).unwrap()).unwrap(), None);
+    }
+
+    #[test]
+    pub fn test_substitutes_from_april_1973() {
+        // 建国記念の日 on Sunday 1973-02-11 predates the amendment effective 1973-04-12
+        let days = super::holidays(1973).unwrap();
+        assert!(!days.iter().any(|h| h.date == NaiveDate::from_ymd_opt(1973, 2, 12).unwrap()));
+        let first = days.iter().find(|h| h.substitute).unwrap();
+        assert_eq!(first.date, NaiveDate::from_ymd_opt(1973, 4, 30).unwrap());
+        assert_eq!(first.name, "振替休日(天皇誕生日)");
+    }
+
+    #[test]
+    pub fn test_holiday_set() {
+        let set = super::HolidaySet::for_year(2024).unwrap();
+        assert_eq!(set.year(), 2024);
+        assert!(set.contains(NaiveDate::from_ymd_opt(2024, 5, 6).unwrap()));
+        assert!(!set.contains(NaiveDate::from_ymd_opt(2024, 5, 7).unwrap()));
+        assert_eq!(set.name(NaiveDate::from_ymd_opt(2024, 11, 3).unwrap()), Some("文化の日"));
+    }
+
+    #[test]
+    pub fn test_today_status() {
+        assert_eq!(super::date_status(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()).unwrap(), (true, Some("元旦".to_string())));
+        assert_eq!(super::date_status(NaiveDate::from_ymd_opt(2024, 1, 2).unwrap()).unwrap(), (false, None));
+        let jst = super::jst();
+        let today = chrono::Utc::now().with_timezone(&jst).date_naive();
+        let (is_holiday, name) = super::today_status(&jst).unwrap();
+        // guard against the date rolling over between the two calls
+        if chrono::Utc::now().with_timezone(&jst).date_naive() == today {
+            assert_eq!(is_holiday, super::is_holiday(today).unwrap());
+            assert_eq!(is_holiday, name.is_some());
+        }
+    }
+
+    #[test]
+    pub fn test_is_holiday_timestamp() {
+        // 2024-01-01T00:00:00+09:00
+        assert!(super::is_holiday_timestamp(1704034800).unwrap());
+        // 2023-12-31T23:59:59+09:00, one second before the JST day boundary
+        assert!(!super::is_holiday_timestamp(1704034799).unwrap());
+        // 2024-01-02T00:00:00+09:00
+        assert!(!super::is_holiday_timestamp(1704121200).unwrap());
+    }
+
+    #[test]
+    pub fn test_leap_day_holiday_in_common_year() {
+        let data = "name,date,relative,condition\n元旦,1/1,false,\nうるう日,2/29,false,\n";
+        let schedule = super::super::timebase::parse_schedule(data.as_bytes()).unwrap();
+        let err = super::prepare_holidays(2023, &schedule).unwrap_err().to_string();
+        assert!(err.contains("うるう日"), "{}", err);
+        assert!(err.contains("2023"), "{}", err);
+
+        let schedule = super::super::timebase::parse_schedule(data.as_bytes()).unwrap();
+        let days = super::prepare_holidays(2024, &schedule).unwrap();
+        assert_eq!(days[1].date, NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+    }
+
+    #[test]
+    pub fn test_unknown_condition_token() {
+        // Conditions are validated when the data is parsed
+        let data = "name,date,relative,condition\n海の日,,true,july:3:mondey\n";
+        let err = super::super::timebase::parse_schedule(data.as_bytes()).unwrap_err().to_string();
+        assert!(err.contains("海の日"), "{}", err);
+        assert!(err.contains("mondey"), "{}", err);
+
+        let data = "name,date,relative,condition\n海の日,,true,jly:3:monday\n";
+        let err = super::super::timebase::parse_schedule(data.as_bytes()).unwrap_err().to_string();
+        assert!(err.contains("jly"), "{}", err);
+    }
+
+    // Compares `holidays()` with the vendored excerpt of the Cabinet Office dataset
+    // (https://www8.cao.go.jp/chosei/shukujitsu/syukujitsu.csv, converted to UTF-8).
+    // The dataset labels every substitute and national holiday as 休日.
+    #[cfg(feature = "official-dataset")]
+    #[test]
+    pub fn test_holidays_match_official_dataset() {
+        use std::collections::BTreeMap;
+        // Names that differ only in wording between this crate and the dataset
+        const ALIASES: [(&str, &str); 1] = [("元旦", "元日")];
+        const OTHER_HOLIDAY: &str = "休日";
+
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/syukujitsu.csv");
+        let mut official: BTreeMap<i32, BTreeMap<NaiveDate, String>> = BTreeMap::new();
+        let mut reader = csv::Reader::from_path(path).unwrap();
+        for record in reader.records() {
+            let record = record.unwrap();
+            let date = NaiveDate::parse_from_str(&record[0], "%Y/%m/%d").unwrap();
+            official.entry(chrono::Datelike::year(&date)).or_default().insert(date, record[1].to_string());
+        }
+        assert!(!official.is_empty());
+
+        let mut mismatches: Vec<String> = Vec::new();
+        for (year, expected) in &official {
+            let actual: BTreeMap<NaiveDate, String> = super::holidays(*year as u32).unwrap().into_iter()
+                .map(|h| {
+                    let name = match h.category {
+                        super::HolidayCategory::Substitute | super::HolidayCategory::National => OTHER_HOLIDAY.to_string(),
+                        _ => ALIASES.iter().find(|(ours, _)| *ours == h.name).map_or(h.name, |(_, theirs)| theirs.to_string()),
+                    };
+                    (h.date, name)
+                })
+                .collect();
+            for date in expected.keys().chain(actual.keys()).collect::<std::collections::BTreeSet<_>>() {
+                let (e, a) = (expected.get(date), actual.get(date));
+                if e != a {
+                    mismatches.push(format!("{}: official {:?}, computed {:?}", date, e, a));
+                }
+            }
+        }
+        assert!(mismatches.is_empty(), "differs from the official dataset:\n{}", mismatches.join("\n"));
+    }
+
+    #[test]
+    pub fn test_holiday_ordering() {
+        use super::{Holiday, HolidayCategory};
+        let holiday = |name: &str, d: u32, substitute: bool| Holiday {
+            name: name.to_string(),
+            date: NaiveDate::from_ymd_opt(2024, 5, d).unwrap(),
+            substitute,
+            category: if substitute { HolidayCategory::Substitute } else { HolidayCategory::Fixed },
+            source: None,
+            confidence: super::Confidence::Enacted,
+        };
+        // Date first
+        assert!(holiday("こどもの日", 5, false) < holiday("振替休日", 6, true));
+        assert!(holiday("憲法記念日", 3, true) < holiday("みどりの日", 4, false));
+        // Statutory before substitute on the same date
+        assert!(holiday("振替休日", 6, false) < holiday("こどもの日", 6, true));
+        // Then by name
+        assert!(holiday("a", 6, false) < holiday("b", 6, false));
+        assert_eq!(holiday("a", 6, false).cmp(&holiday("a", 6, false)), std::cmp::Ordering::Equal);
+    }
+
+    #[test]
+    pub fn test_merge_sorted() {
+        let custom = vec![super::Holiday {
+            name: "創立記念日".to_string(),
+            date: NaiveDate::from_ymd_opt(2024, 5, 1).unwrap(),
+            substitute: false,
+            category: super::HolidayCategory::Fixed,
+            source: None,
+            confidence: super::Confidence::Enacted,
+        }];
+        let merged = super::merge_sorted(super::holidays(2024).unwrap(), custom);
+        assert_eq!(merged.len(), 22);
+        assert_eq!(merged[7].name, "創立記念日");
+        assert!(merged.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(super::merge_sorted(Vec::new(), Vec::new()), Vec::new());
+    }
+
+    #[test]
+    pub fn test_total_days_off() {
+        assert_eq!(super::total_days_off(2024, false).unwrap(), 21);
+        // 104 weekend days, plus the 14 holidays that fall on a weekday
+        assert_eq!(super::total_days_off(2024, true).unwrap(), 118);
+        // 2026-09-22 (国民の休日) and 2026-05-06 (振替休日) are counted once each
+        assert_eq!(super::total_days_off(2026, false).unwrap(), 18);
+    }
+
+    #[test]
+    pub fn test_output_keys() {
+        let keys = |fields: super::Fields| -> Vec<String> {
+            let json = super::OutputBuilder::new(super::OutputFormat::JSON).fields(fields).build(2024).unwrap();
+            let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+            let items = value.as_array().expect("top level is an array");
+            items[0].as_object().unwrap().keys().cloned().collect()
+        };
+        // serde_json::Map keeps keys sorted
+        assert_eq!(keys(super::Fields::default()), vec!["date", "name", "substitute"]);
+        assert_eq!(keys(super::Fields::ALL), vec!["category", "cluster_index", "confidence", "date", "datetime_rfc3339", "day", "era", "law_reference", "long_weekend_length", "month", "name", "name_en", "substitute", "time"]);
+
+        let holiday = serde_json::to_value(&super::holidays(2024).unwrap()[3]).unwrap();
+        let keys: Vec<&String> = holiday.as_object().unwrap().keys().collect();
+        assert_eq!(keys, vec!["category", "confidence", "date", "name", "source", "substitute"]);
+    }
+
+    #[test]
+    pub fn test_weekday_distribution() {
+        use chrono::Weekday;
+        assert_eq!(super::holiday_date_by_name(2024, "海の日").unwrap(), NaiveDate::from_ymd_opt(2024, 7, 15));
+        assert_eq!(super::holiday_date_by_name(2024, "存在しない日").unwrap(), None);
+
+        // 元旦 2024-2030: Mon, Wed, Thu, Fri, Sat, Mon, Tue
+        let counts = super::weekday_distribution("元旦", 2024, 2030).unwrap();
+        assert_eq!(counts.get(&Weekday::Mon), Some(&2));
+        assert_eq!(counts.get(&Weekday::Sat), Some(&1));
+        assert_eq!(counts.get(&Weekday::Sun), None);
+        assert_eq!(counts.values().sum::<u32>(), 7);
+
+        // Happy Monday holidays always fall on Monday
+        let counts = super::weekday_distribution("成人の日", 2000, 2050).unwrap();
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[&Weekday::Mon], 51);
+    }
+
+    #[test]
+    pub fn test_holiday_bitmap() {
+        let bitmap = super::holiday_bitmap(2024).unwrap();
+        assert_eq!(bitmap.len(), 366);
+        assert_eq!(bitmap.iter().filter(|&&v| v).count(), 21);
+        assert!(bitmap[0]);
+        assert!(!bitmap[1]);
+        // 2024-11-23 is a day later than in a common year
+        assert!(bitmap[327]);
+
+        let bitmap = super::holiday_bitmap(2023).unwrap();
+        assert_eq!(bitmap.len(), 365);
+        assert!(bitmap[326]);
+    }
+
+    #[test]
+    pub fn test_business_days() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        // Friday 2024-02-09, then the weekend and 振替休日(建国記念の日) on Monday
+        assert_eq!(super::next_business_day(date(2024, 2, 9)).unwrap(), date(2024, 2, 13));
+        // Across New Year's: 2025-12-31 is a Wednesday, 2026-01-01 a holiday
+        let days: Vec<NaiveDate> = super::business_days_from(date(2025, 12, 30))
+            .take(4)
+            .collect::<anyhow::Result<_>>()
+            .unwrap();
+        assert_eq!(days, vec![date(2025, 12, 31), date(2026, 1, 2), date(2026, 1, 5), date(2026, 1, 6)]);
+    }
+
+    #[test]
+    pub fn test_calendar_trait() {
+        use super::{Calendar, Holiday, HolidayCategory, JapanNationalCalendar};
+        let national = JapanNationalCalendar::default();
+        assert_eq!(national.holidays(2024).unwrap(), super::holidays(2024).unwrap());
+
+        // A company calendar adding its founding day to the national holidays
+        struct CompanyCalendar;
+        impl Calendar for CompanyCalendar {
+            fn holidays(&self, year: u32) -> anyhow::Result<Vec<Holiday>> {
+                let founding = vec![Holiday {
+                    name: "創立記念日".to_string(),
+                    date: NaiveDate::from_ymd_opt(year as i32, 6, 1).unwrap(),
+                    substitute: false,
+                    category: HolidayCategory::Fixed,
+                    source: None,
+                    confidence: super::Confidence::Enacted,
+                }];
+                Ok(super::merge_sorted(JapanNationalCalendar::default().holidays(year)?, founding))
+            }
+        }
+        let calendars: Vec<Box<dyn Calendar>> = vec![Box::new(national), Box::new(CompanyCalendar)];
+        let counts: Vec<usize> = calendars.iter().map(|c| c.holidays(2024).unwrap().len()).collect();
+        assert_eq!(counts, vec![21, 22]);
+    }
+
+    #[test]
+    pub fn test_combined_calendar() {
+        use super::{Calendar, CombinedCalendar, Holiday, HolidayCategory, JapanNationalCalendar};
+        struct CompanyCalendar;
+        impl Calendar for CompanyCalendar {
+            fn holidays(&self, year: u32) -> anyhow::Result<Vec<Holiday>> {
+                let holiday = |name: &str, m, d| Holiday {
+                    name: name.to_string(),
+                    date: NaiveDate::from_ymd_opt(year as i32, m, d).unwrap(),
+                    substitute: false,
+                    category: HolidayCategory::Fixed,
+                    source: None,
+                    confidence: super::Confidence::Enacted,
+                };
+                Ok(vec![holiday("年始休暇", 1, 1), holiday("創立記念日", 6, 2)])
+            }
+        }
+        let combined = CombinedCalendar {
+            sources: vec![Box::new(JapanNationalCalendar::default()), Box::new(CompanyCalendar)],
+        };
+        let days = combined.holidays(2024).unwrap();
+        assert_eq!(days.len(), 23);
+        // Both sources have January 1; the national calendar is listed first
+        assert_eq!(days[0].name, "元旦・年始休暇");
+        assert_eq!(days.iter().filter(|h| h.date == days[0].date).count(), 1);
+        // 2024-06-02 is a Sunday
+        let june: Vec<(&str, String)> = days.iter()
+            .filter(|h| h.date.format("%m").to_string() == "06")
+            .map(|h| (h.name.as_str(), h.date.to_string()))
+            .collect();
+        assert_eq!(june, vec![("創立記念日", "2024-06-02".to_string()), ("振替休日(創立記念日)", "2024-06-03".to_string())]);
+        assert!(days.windows(2).all(|w| w[0].date < w[1].date));
+
+        // January 2 falls between 元旦 and the company's January 3
+        struct NewYearCalendar;
+        impl Calendar for NewYearCalendar {
+            fn holidays(&self, year: u32) -> anyhow::Result<Vec<Holiday>> {
+                Ok(vec![Holiday {
+                    name: "年始休暇".to_string(),
+                    date: NaiveDate::from_ymd_opt(year as i32, 1, 3).unwrap(),
+                    substitute: false,
+                    category: HolidayCategory::Fixed,
+                    source: None,
+                    confidence: super::Confidence::Enacted,
+                }])
+            }
+        }
+        let combined = CombinedCalendar {
+            sources: vec![Box::new(JapanNationalCalendar::default()), Box::new(NewYearCalendar)],
+        };
+        let january: Vec<(String, HolidayCategory)> = combined.holidays(2024).unwrap().into_iter()
+            .take(3)
+            .map(|h| (h.name, h.category))
+            .collect();
+        assert_eq!(january, vec![
+            ("元旦".to_string(), HolidayCategory::Fixed),
+            ("国民の休日".to_string(), HolidayCategory::National),
+            ("年始休暇".to_string(), HolidayCategory::Fixed),
+        ]);
+    }
+
+    #[test]
+    pub fn test_holidays_ymd() {
+        let days = super::holidays_ymd(2024).unwrap();
+        assert_eq!(days.len(), 21);
+        assert_eq!(days[0], (2024, 1, 1, "元旦".to_string()));
+        assert_eq!(days[3], (2024, 2, 12, "振替休日(建国記念の日)".to_string()));
+    }
+
+    #[test]
+    pub fn test_holidays_as_datetimes() {
+        let utc = super::holidays_as_datetimes(2024, &chrono::Utc).unwrap();
+        assert_eq!(utc.len(), 21);
+        assert_eq!(utc[0].0.to_rfc3339(), "2024-01-01T00:00:00+00:00");
+        assert_eq!(utc[0].1, "元旦");
+    }
+
+    #[cfg(feature = "chrono-tz")]
+    #[test]
+    pub fn test_holidays_as_datetimes_iana() {
+        let tokyo = super::holidays_as_datetimes(2024, &chrono_tz::Asia::Tokyo).unwrap();
+        assert_eq!(tokyo[0].0.to_rfc3339(), "2024-01-01T00:00:00+09:00");
+        assert_eq!(tokyo[0].0.timestamp(), super::jst_midnight(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()).timestamp());
+        let new_york = super::holidays_as_datetimes(2024, &chrono_tz::America::New_York).unwrap();
+        assert_eq!(new_york[0].0.to_rfc3339(), "2024-01-01T00:00:00-05:00");
+        assert_eq!(new_york[11].0.to_rfc3339(), "2024-07-15T00:00:00-04:00");
+    }
+
+    #[test]
+    pub fn test_nearest_holiday() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(super::nearest_holiday(date(2024, 12, 30)).unwrap(), (date(2025, 1, 1), "元旦".to_string(), 2));
+        assert_eq!(super::nearest_holiday(date(2025, 1, 3)).unwrap(), (date(2025, 1, 1), "元旦".to_string(), -2));
+        assert_eq!(super::nearest_holiday(date(2024, 11, 28)).unwrap().2, -5);
+        assert_eq!(super::nearest_holiday(date(2024, 5, 5)).unwrap(), (date(2024, 5, 5), "こどもの日".to_string(), 0));
+        // 敬老の日 three days back, 秋分の日 three days ahead
+        assert_eq!(super::nearest_holiday(date(2024, 9, 19)).unwrap(), (date(2024, 9, 22), "秋分の日".to_string(), 3));
+        // The years next to the supported range are skipped, not an error
+        assert_eq!(super::nearest_holiday(date(1948, 10, 1)).unwrap(), (date(1948, 9, 23), "秋分の日".to_string(), -8));
+        assert_eq!(super::nearest_holiday(date(2099, 12, 31)).unwrap().1, "勤労感謝の日");
+        assert!(super::nearest_holiday(date(2100, 1, 1)).is_err());
+    }
+
+    #[test]
+    pub fn test_longest_break() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        // Golden Week: Friday 5/3 through 振替休日 on Monday 5/6
+        assert_eq!(super::longest_break(2024).unwrap(), (date(2024, 5, 3), date(2024, 5, 6), 4));
+        // Golden Week and Silver Week both last five days; the earlier one wins
+        assert_eq!(super::longest_break(2026).unwrap(), (date(2026, 5, 2), date(2026, 5, 6), 5));
+    }
+
+    #[test]
+    pub fn test_has_silver_week() {
+        assert!(super::has_silver_week(2015).unwrap());
+        assert!(super::has_silver_week(2026).unwrap());
+        assert!(!super::has_silver_week(2024).unwrap());
+    }
+
+    // Deserialized form of an output item, for round trips
+    #[derive(serde::Deserialize)]
+    struct ParsedItem {
+        name: String,
+        date: NaiveDate,
+        substitute: bool,
+    }
+
+    fn expected_items(year: u32) -> Vec<(String, NaiveDate, bool)> {
+        super::holidays(year).unwrap().into_iter()
+            .map(|h| (h.name, h.date, h.substitute))
+            .collect()
+    }
+
+    fn parsed(items: Vec<ParsedItem>) -> Vec<(String, NaiveDate, bool)> {
+        items.into_iter().map(|i| (i.name, i.date, i.substitute)).collect()
+    }
+
+    #[test]
+    pub fn test_yaml_round_trip() {
+        let yaml = super::holiday(super::OutputFormat::YAML, 2024).unwrap();
+        let items: Vec<ParsedItem> = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed(items), expected_items(2024));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    pub fn test_toml_round_trip() {
+        #[derive(serde::Deserialize)]
+        struct Document {
+            holidays: Vec<ParsedItem>,
+        }
+        let toml = super::holiday(super::OutputFormat::TOML, 2024).unwrap();
+        assert!(toml.starts_with("[[holidays]]\nname = \"元旦\"\n"), "{}", toml);
+        let document: Document = toml::from_str(&toml).unwrap();
+        assert_eq!(parsed(document.holidays), expected_items(2024));
+    }
+
+    #[test]
+    pub fn test_validate_year() {
+        for year in [1990, 2019, 2024, 2025, 2026] {
+            assert_eq!(super::validate_year(year), Ok(()));
+        }
+
+        let mut days = super::holidays(2024).unwrap();
+        // Move 振替休日(建国記念の日) from Monday to Tuesday and duplicate 元旦
+        days[3].date = NaiveDate::from_ymd_opt(2024, 2, 13).unwrap();
+        days.push(super::Holiday { name: "元日".to_string(), ..super::holidays(2024).unwrap().remove(0) });
+        let violations = super::check_holidays(2024, &days);
+        assert_eq!(violations, vec![
+            "2024-01-01: more than one statutory holiday".to_string(),
+            "2024-02-13: substitute 振替休日(建国記念の日) does not follow a holiday on Sunday".to_string(),
+        ]);
+
+        let mut days = super::holidays(2024).unwrap();
+        days.retain(|h| h.name != "春分の日");
+        assert_eq!(super::check_holidays(2024, &days), vec!["2024: 0 春分の日 found, expected one".to_string()]);
+        assert_eq!(super::validate_year(1948), Ok(()));
+        assert!(super::validate_year(2100).is_err());
+    }
+
+    #[test]
+    pub fn test_nth_weekday_of_month() {
+        use chrono::Weekday;
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+        // 2024-07-01 is a Monday
+        assert_eq!(super::nth_weekday_of_month(2024, 7, Weekday::Mon, 1), date(2024, 7, 1));
+        assert_eq!(super::nth_weekday_of_month(2024, 7, Weekday::Mon, 3), date(2024, 7, 15));
+        assert_eq!(super::nth_weekday_of_month(2024, 7, Weekday::Sun, 1), date(2024, 7, 7));
+        assert_eq!(super::nth_weekday_of_month(2024, 2, Weekday::Thu, 5), date(2024, 2, 29));
+        assert_eq!(super::nth_weekday_of_month(2023, 2, Weekday::Thu, 5), None);
+        assert_eq!(super::nth_weekday_of_month(2024, 7, Weekday::Mon, 0), None);
+        assert_eq!(super::nth_weekday_of_month(2024, 7, Weekday::Mon, u32::MAX), None);
+    }
+
+    #[test]
+    pub fn test_holiday_output_law_reference() {
+        let builder = super::OutputBuilder::new(super::OutputFormat::NDJSON).fields(super::Fields::LAW_REFERENCE);
+        let result = builder.build(2024).unwrap();
+        let references: Vec<(String, String)> = result.lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .map(|v| (v["name"].as_str().unwrap().to_string(), v["law_reference"].as_str().unwrap().to_string()))
+            .collect();
+        assert_eq!(references.len(), 21);
+        assert_eq!(references[0], ("元旦".to_string(), "国民の祝日に関する法律 第2条".to_string()));
+        assert_eq!(references[3], ("振替休日(建国記念の日)".to_string(), "国民の祝日に関する法律 第3条第2項".to_string()));
+        assert_eq!(references[5], ("春分の日".to_string(), "国民の祝日に関する法律 第2条".to_string()));
+
+        let national = builder.build(2026).unwrap();
+        assert!(national.contains("\"name\":\"国民の休日\",\"date\":\"2026-09-22\",\"substitute\":false,\"law_reference\":\"国民の祝日に関する法律 第3条第3項\""), "{}", national);
+    }
+
+    #[test]
+    pub fn test_effective_days_off() {
+        let days = super::effective_days_off(2024).unwrap();
+        // 21 holidays, less five on Sunday and みどりの日 and 勤労感謝の日 on Saturday
+        assert_eq!(days.len(), 14);
+        let names: Vec<&str> = days.iter().map(|d| d.name.as_str()).collect();
+        assert!(!names.contains(&"みどりの日"));
+        assert!(!names.contains(&"勤労感謝の日"));
+        assert!(!names.contains(&"こどもの日"));
+        assert!(names.contains(&"振替休日(こどもの日)"));
+        assert_eq!(days[0].date, NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+    }
+
+    #[test]
+    pub fn test_confidence() {
+        use super::Confidence;
+        assert!(super::holidays(2027).unwrap().iter().all(|h| h.confidence == Confidence::Enacted));
+        let days = super::holidays(2030).unwrap();
+        let predicted: Vec<&str> = days.iter()
+            .filter(|h| h.confidence == Confidence::Predicted)
+            .map(|h| h.name.as_str())
+            .collect();
+        assert_eq!(predicted, vec!["春分の日", "秋分の日"]);
+        // 2032: 国民の休日 on 9/21 between 敬老の日 and the predicted 秋分の日
+        let days = super::holidays(2032).unwrap();
+        let national = days.iter().find(|h| h.category == super::HolidayCategory::National).unwrap();
+        assert_eq!(national.date, NaiveDate::from_ymd_opt(2032, 9, 21).unwrap());
+        assert_eq!(national.confidence, Confidence::Predicted);
+
+        let output = super::OutputBuilder::new(super::OutputFormat::NDJSON).fields(super::Fields::CONFIDENCE).build(2030).unwrap();
+        assert!(output.contains("\"name\":\"春分の日\",\"date\":\"2030-03-20\",\"substitute\":false,\"confidence\":\"predicted\""), "{}", output);
+    }
+
+    #[test]
+    pub fn test_break_containing() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let golden_week = Some((date(2024, 5, 3), date(2024, 5, 6)));
+        assert_eq!(super::break_containing(date(2024, 5, 3)).unwrap(), golden_week);
+        assert_eq!(super::break_containing(date(2024, 5, 5)).unwrap(), golden_week);
+        assert_eq!(super::break_containing(date(2024, 5, 7)).unwrap(), None);
+        // An ordinary weekend
+        assert_eq!(super::break_containing(date(2024, 6, 8)).unwrap(), Some((date(2024, 6, 8), date(2024, 6, 9))));
+        // Across New Year: Saturday 2022-12-31 through 振替休日 on 2023-01-02
+        assert_eq!(super::break_containing(date(2023, 1, 1)).unwrap(), Some((date(2022, 12, 31), date(2023, 1, 2))));
+        // Within a month of the first and last supported days
+        assert_eq!(super::break_containing(date(1948, 1, 10)).unwrap(), Some((date(1948, 1, 10), date(1948, 1, 11))));
+        assert_eq!(super::break_containing(date(2099, 12, 27)).unwrap(), Some((date(2099, 12, 26), date(2099, 12, 27))));
+        assert_eq!(super::break_containing(date(2099, 12, 31)).unwrap(), None);
+    }
+
+    #[test]
+    pub fn test_equinox_confidence_boundary() {
+        use super::{Confidence, HolidayCategory, EQUINOX_OFFICIAL_UNTIL};
+        let equinox_confidence = |year| -> Vec<Confidence> {
+            super::holidays(year).unwrap().into_iter()
+                .filter(|h| h.category == HolidayCategory::Equinox)
+                .map(|h| h.confidence)
+                .collect()
+        };
+        assert_eq!(equinox_confidence(EQUINOX_OFFICIAL_UNTIL), vec![Confidence::Enacted; 2]);
+        assert_eq!(equinox_confidence(EQUINOX_OFFICIAL_UNTIL + 1), vec![Confidence::Predicted; 2]);
+        // The observatory projection up to 2050 is not an announcement
+        assert_eq!(equinox_confidence(2050), vec![Confidence::Predicted; 2]);
+        assert_eq!(Confidence::for_equinox(1990), Confidence::Enacted);
+    }
+
+    #[test]
+    pub fn test_holiday_output_descending() {
+        let result = super::OutputBuilder::new(super::OutputFormat::CSV).sort(super::SortOrder::Descending).build(2024).unwrap();
+        let dates: Vec<&str> = result.lines().skip(1).map(|line| line.split(',').nth(1).unwrap()).collect();
+        assert_eq!(dates.len(), 21);
+        assert_eq!(dates[0], "2024-11-23");
+        assert_eq!(dates[20], "2024-01-01");
+        assert!(dates.windows(2).all(|w| w[0] > w[1]));
+    }
+
+    #[test]
+    pub fn test_holiday_clusters() {
+        let names = |year| -> Vec<Vec<String>> {
+            super::holiday_clusters(year).unwrap().into_iter()
+                .map(|c| c.into_iter().map(|h| h.name).collect())
+                .collect()
+        };
+        let clusters = names(2024);
+        assert!(clusters.contains(&vec!["憲法記念日".to_string(), "みどりの日".to_string(), "こどもの日".to_string(), "振替休日(こどもの日)".to_string()]));
+        assert!(clusters.contains(&vec!["昭和の日".to_string()]));
+        assert!(clusters.contains(&vec!["秋分の日".to_string(), "振替休日(秋分の日)".to_string()]));
+        assert_eq!(clusters.iter().map(Vec::len).sum::<usize>(), 21);
+        // Silver Week
+        assert!(names(2026).contains(&vec!["敬老の日".to_string(), "国民の休日".to_string(), "秋分の日".to_string()]));
+
+        // A Friday and the following Monday are bridged by the weekend, a Wednesday is not
+        let day = |d| super::Holiday {
+            date: NaiveDate::from_ymd_opt(2024, 2, d).unwrap(),
+            ..super::holidays(2024).unwrap().remove(3)
+        };
+        let days = vec![day(23), day(26), day(28)];
+        let clusters: Vec<usize> = super::cluster_holidays(days).iter().map(Vec::len).collect();
+        assert_eq!(clusters, vec![2, 1]);
+    }
+
+    #[test]
+    pub fn test_unusual_substitutes() {
+        let dates = |year| -> Vec<String> {
+            super::unusual_substitutes(year).unwrap().into_iter().map(|h| h.date.to_string()).collect()
+        };
+        // 振替休日 on 5/6 recurs in Golden Week, and 2024-05-06 is not listed
+        assert_eq!(dates(2024), vec!["2024-02-12", "2024-08-12", "2024-09-23", "2024-11-04"]);
+        assert!(super::holidays(2024).unwrap().iter().any(|h| h.substitute && h.date.to_string() == "2024-05-06"));
+        // No holiday of 2022 falls on Sunday
+        assert_eq!(dates(2022).len(), 0);
+        // 山の日 of 2021 was moved to Sunday 8/8 for the Olympics
+        assert_eq!(dates(2021), vec!["2021-08-09"]);
+        // The window is cut at the ends of the supported years
+        use super::super::year::Year;
+        assert!(super::unusual_substitutes(Year::MIN).is_ok());
+        assert!(super::unusual_substitutes(Year::MAX).is_ok());
+    }
+
+    #[test]
+    pub fn test_substitute_across_new_year() {
+        use super::{Calendar, CombinedCalendar, Holiday, HolidayCategory, JapanNationalCalendar};
+        struct YearEndCalendar;
+        impl Calendar for YearEndCalendar {
+            fn holidays(&self, year: u32) -> anyhow::Result<Vec<Holiday>> {
+                Ok(vec![Holiday {
+                    name: "大晦日".to_string(),
+                    date: NaiveDate::from_ymd_opt(year as i32, 12, 31).unwrap(),
+                    substitute: false,
+                    category: HolidayCategory::Fixed,
+                    source: None,
+                    confidence: super::Confidence::Enacted,
+                }])
+            }
+        }
+        let combined = CombinedCalendar {
+            sources: vec![Box::new(JapanNationalCalendar::default()), Box::new(YearEndCalendar)],
+        };
+        // 2023-12-31 is a Sunday and 2024-01-01 is 元旦, so the substitute is 2024-01-02
+        let jan2 = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        let days = combined.holidays(2024).unwrap();
+        assert_eq!(days[1].date, jan2);
+        assert_eq!(days[1].name, "振替休日(大晦日)");
+        assert!(days.iter().all(|h| h.date.format("%Y").to_string() == "2024"));
+        assert!(combined.holidays(2023).unwrap().iter().all(|h| h.date < jan2));
+        assert_eq!(combined.substitute_source(jan2).unwrap(), Some("大晦日".to_string()));
+        assert_eq!(JapanNationalCalendar::default().substitute_source(jan2).unwrap(), None);
+    }
+
+    #[test]
+    pub fn test_olympics_relocations() {
+        use chrono::Datelike;
+        let summer = |year| -> Vec<(String, String)> {
+            super::holidays(year).unwrap().into_iter()
+                .filter(|h| (7..=8).contains(&h.date.month()))
+                .map(|h| (h.name, h.date.to_string()))
+                .collect()
+        };
+        assert_eq!(summer(2021), vec![
+            ("海の日".to_string(), "2021-07-22".to_string()),
+            ("スポーツの日".to_string(), "2021-07-23".to_string()),
+            ("山の日".to_string(), "2021-08-08".to_string()),
+            ("振替休日(山の日)".to_string(), "2021-08-09".to_string()),
+        ]);
+        assert_eq!(summer(2020), vec![
+            ("海の日".to_string(), "2020-07-23".to_string()),
+            ("スポーツの日".to_string(), "2020-07-24".to_string()),
+            ("山の日".to_string(), "2020-08-10".to_string()),
+        ]);
+        // スポーツの日 is not on its recurring second Monday of October in either year
+        assert!(super::holidays(2021).unwrap().iter().all(|h| h.date.month() != 10));
+        assert_eq!(super::validate_year(2021), Ok(()));
+    }
+
+    #[test]
+    pub fn test_relative_holidays_resolve_every_year() {
+        use chrono::Datelike;
+        let schedule = super::schedule().unwrap();
+        let relative: Vec<_> = schedule.iter().filter(|d| d.relative).collect();
+        assert!(!relative.is_empty());
+        for d in relative {
+            let condition = d.condition.unwrap_or_else(|| panic!("{} has no condition", d.name));
+            let (month, weekday) = (condition.month, condition.weekday);
+            for year in 1948..=2100 {
+                let date = super::nth_weekday_of_month(year, month, weekday, condition.nth)
+                    .unwrap_or_else(|| panic!("{} does not resolve in {}", d.name, year));
+                assert_eq!((date.month(), date.weekday()), (month, weekday), "{} in {}", d.name, year);
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_holidays_this_week() {
+        use chrono::Weekday;
+        let week = |y, m, d, start| -> Vec<String> {
+            super::holidays_this_week(NaiveDate::from_ymd_opt(y, m, d).unwrap(), start).unwrap()
+                .into_iter().map(|h| h.date.to_string()).collect()
+        };
+        // Thursday 2026-01-01, in the week from Monday 2025-12-29
+        assert_eq!(week(2026, 1, 1, Weekday::Mon), vec!["2026-01-01"]);
+        assert_eq!(week(2025, 12, 29, Weekday::Mon), vec!["2026-01-01"]);
+        assert_eq!(week(2026, 1, 5, Weekday::Mon), Vec::<String>::new());
+        // Sunday 2026-01-04 starts a new week when weeks begin on Sunday
+        assert_eq!(week(2026, 1, 3, Weekday::Sun), vec!["2026-01-01"]);
+        assert_eq!(week(2026, 1, 4, Weekday::Sun), Vec::<String>::new());
+        // Golden Week 2024 runs from Wednesday to Monday
+        assert_eq!(week(2024, 5, 1, Weekday::Mon), vec!["2024-04-29", "2024-05-03", "2024-05-04", "2024-05-05"]);
+    }
+
+    #[test]
+    pub fn test_holiday_rule() {
+        use super::HolidayRule;
+        use chrono::Weekday;
+        let rule = super::holiday_rule("成人の日").unwrap().unwrap();
+        assert_eq!(rule, HolidayRule::NthWeekday { month: 1, n: 2, weekday: Weekday::Mon });
+        assert_eq!(rule.to_string(), "1月第2月曜日");
+        let rule = super::holiday_rule("建国記念の日").unwrap().unwrap();
+        assert_eq!(rule, HolidayRule::Fixed { month: 2, day: 11 });
+        assert_eq!(rule.to_string(), "2月11日");
+        assert_eq!(super::holiday_rule("秋分の日").unwrap().map(|r| r.to_string()), Some("秋分日".to_string()));
+        assert_eq!(super::holiday_rule("振替休日").unwrap(), None);
+    }
+
+    #[test]
+    pub fn test_fixed_holiday_weekdays() {
+        use chrono::Weekday;
+        let weekdays = super::fixed_holiday_weekdays(2024).unwrap();
+        assert_eq!(weekdays.len(), 10);
+        assert_eq!(weekdays[0], ("元旦".to_string(), Weekday::Mon));
+        assert_eq!(weekdays[1], ("建国記念の日".to_string(), Weekday::Sun));
+        assert_eq!(weekdays.last(), Some(&("勤労感謝の日".to_string(), Weekday::Sat)));
+        assert!(weekdays.iter().all(|(name, _)| name != "成人の日" && name != "春分の日"));
+    }
+
+    #[test]
+    pub fn test_parse_month_day() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        assert_eq!(super::parse_month_day(2024, "1/1").unwrap(), date);
+        assert_eq!(super::parse_month_day(2024, "01/01").unwrap(), date);
+        let err = super::parse_month_day(2024, "1/1/1").unwrap_err().to_string();
+        assert!(err.contains("3 components"), "{}", err);
+        assert!(super::parse_month_day(2024, "1").unwrap_err().to_string().contains("1 components"));
+        assert!(super::parse_month_day(2024, "1/x").unwrap_err().to_string().contains("day \"x\""));
+        assert!(super::parse_month_day(2024, "+1/1").is_err());
+        assert!(super::parse_month_day(2023, "2/29").unwrap_err().to_string().contains("2023"));
+
+        let data = "name,date,relative,condition\n元旦,1/1/2024,false,\n";
+        let schedule = super::super::timebase::parse_schedule(data.as_bytes()).unwrap();
+        let err = super::prepare_holidays(2024, &schedule).unwrap_err().to_string();
+        assert!(err.contains("元旦") && err.contains("1/1/2024"), "{}", err);
+    }
+
+    #[test]
+    pub fn test_nth_business_day_of_month() {
+        let date = |m, d| Some(NaiveDate::from_ymd_opt(2024, m, d).unwrap());
+        // 2024-01-01 is 元旦 and 2024-01-08 成人の日
+        assert_eq!(super::nth_business_day_of_month(2024, 1, 1).unwrap(), date(1, 2));
+        assert_eq!(super::nth_business_day_of_month(2024, 1, 5).unwrap(), date(1, 9));
+        // Golden Week leaves 2024-05-01 and 2024-05-02 before 2024-05-07
+        assert_eq!(super::nth_business_day_of_month(2024, 5, 3).unwrap(), date(5, 7));
+        assert_eq!(super::nth_business_day_of_month(2024, 5, 21).unwrap(), date(5, 31));
+        assert_eq!(super::nth_business_day_of_month(2024, 5, 22).unwrap(), None);
+        assert_eq!(super::nth_business_day_of_month(2024, 5, 0).unwrap(), None);
+        assert!(super::nth_business_day_of_month(2024, 13, 1).is_err());
+    }
+
+    #[test]
+    pub fn test_business_day_on_or_before() {
+        let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+        assert_eq!(super::business_day_on_or_before(date(1, 25)).unwrap(), date(1, 25));
+        // Sunday 2024-02-25 goes back to Friday 2024-02-23, which is 天皇誕生日
+        assert_eq!(super::business_day_on_or_before(date(2, 25)).unwrap(), date(2, 22));
+        // Across New Year: 2024-01-01 is 元旦 after a weekend
+        assert_eq!(super::business_day_on_or_before(date(1, 1)).unwrap(), NaiveDate::from_ymd_opt(2023, 12, 29).unwrap());
+    }
+
+    #[test]
+    pub fn test_historical_holidays() {
+        let dates = |year, name: &str| -> Vec<String> {
+            super::holidays(year).unwrap().into_iter().filter(|h| h.name == name).map(|h| h.date.to_string()).collect()
+        };
+        assert_eq!(dates(1988, "天皇誕生日"), vec!["1988-04-29"]);
+        assert_eq!(dates(2018, "天皇誕生日"), vec!["2018-12-23"]);
+        assert_eq!(dates(2019, "天皇誕生日"), Vec::<String>::new());
+        assert_eq!(dates(1999, "成人の日"), vec!["1999-01-15"]);
+        assert_eq!(dates(2019, "体育の日"), vec!["2019-10-14"]);
+        assert_eq!(dates(2019, "スポーツの日"), Vec::<String>::new());
+        // Before 1986 no day became a 国民の休日
+        assert_eq!(dates(1985, "国民の休日"), Vec::<String>::new());
+        assert_eq!(dates(1988, "国民の休日"), vec!["1988-05-04"]);
+        let names: Vec<String> = super::holidays(1948).unwrap().into_iter().map(|h| h.name).collect();
+        assert_eq!(names, vec!["秋分の日", "文化の日", "勤労感謝の日"]);
+    }
+
+    #[test]
+    pub fn test_holidays_as_of() {
+        let emperor = |target, law| -> Vec<String> {
+            super::holidays_as_of(target, law).unwrap().into_iter()
+                .filter(|h| h.name == "天皇誕生日")
+                .map(|h| h.date.to_string())
+                .collect()
+        };
+        assert_eq!(emperor(2018, 2018), vec!["2018-12-23"]);
+        assert_eq!(emperor(2018, 2025), vec!["2018-02-23"]);
+        // The law of the holidays' own year matches `holidays`
+        let own: Vec<String> = super::holidays_as_of(2024, 2024).unwrap().into_iter().map(|h| h.date.to_string()).collect();
+        let current: Vec<String> = super::holidays(2024).unwrap().into_iter().map(|h| h.date.to_string()).collect();
+        assert_eq!(own, current);
+        // No substitute holidays under the law of 1970
+        assert!(super::holidays_as_of(2024, 1970).unwrap().iter().all(|h| !h.substitute));
+        // 2021 under the 2019 law: no Olympics relocation, and 体育の日 instead of スポーツの日
+        let names: Vec<String> = super::holidays_as_of(2021, 2019).unwrap().into_iter().map(|h| h.name).collect();
+        assert!(names.contains(&"体育の日".to_string()) && !names.contains(&"スポーツの日".to_string()));
+    }
+
+    #[test]
+    pub fn test_years_holiday_on_weekend() {
+        assert_eq!(super::years_holiday_on_weekend("建国記念の日", 2020, 2029).unwrap(), vec![2023, 2024, 2029]);
+        // 海の日 is always a Monday since 2003
+        assert_eq!(super::years_holiday_on_weekend("海の日", 2020, 2029).unwrap(), Vec::<u32>::new());
+        // 山の日 only exists since 2016
+        assert_eq!(super::years_holiday_on_weekend("山の日", 2010, 2016).unwrap(), Vec::<u32>::new());
+    }
+
+    #[test]
+    pub fn test_holidays_epoch() {
+        let epoch = super::holidays_epoch(2024).unwrap();
+        assert_eq!(epoch.len(), 21);
+        // 2024-01-01T00:00:00+09:00, the same instant is_holiday_timestamp starts the day at
+        assert_eq!(epoch[0], (1704034800, "元旦".to_string()));
+        assert!(super::is_holiday_timestamp(epoch[0].0).unwrap());
+        assert!(epoch.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    pub fn test_year_notes() {
+        let json = super::holiday(super::OutputFormat::JSON, 2019).unwrap();
+        assert!(!json.contains("天皇誕生日"));
+        assert_eq!(super::year_notes(2019).unwrap(), vec!["2019 has no 天皇誕生日: it was on 12/23 until 2018 and is on 2/23 from 2020"]);
+        assert_eq!(super::year_notes(2018).unwrap(), Vec::<String>::new());
+        assert_eq!(super::year_notes(2024).unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    pub fn test_holidays_value() {
+        let value = super::holidays_value(2024).unwrap();
+        assert_eq!(value["year"], 2024);
+        let items = value["holidays"].as_array().unwrap();
+        assert_eq!(items.len(), 21);
+        assert_eq!(items[0]["name"], "元旦");
+        let json: serde_json::Value = serde_json::from_str(&super::holiday(super::OutputFormat::JSON, 2024).unwrap()).unwrap();
+        assert_eq!(value["holidays"], json);
+        assert_eq!(value["data_hash"], super::data_hash());
+    }
+
+    #[test]
+    pub fn test_holidays_count() {
+        assert_eq!(super::holidays_count(2024).unwrap(), 21);
+        assert_eq!(super::holidays_count(2026).unwrap(), super::holiday_map(2026).unwrap().len());
+        assert_eq!(super::holidays_count(1948).unwrap(), 3);
+    }
+
+    #[test]
+    pub fn test_include_equinoxes() {
+        let names = |builder: super::OutputBuilder| -> Vec<String> {
+            builder.build_items(2024).unwrap().into_iter().map(|h| h.name).collect()
+        };
+        let all = names(super::OutputBuilder::new(super::OutputFormat::JSON));
+        assert!(all.contains(&"春分の日".to_string()) && all.contains(&"秋分の日".to_string()));
+        let without = names(super::OutputBuilder::new(super::OutputFormat::JSON).include_equinoxes(false));
+        assert!(!without.contains(&"春分の日".to_string()) && !without.contains(&"秋分の日".to_string()));
+        assert_eq!(without.len(), all.len() - 2);
+        // The substitute for 秋分の日 on Sunday 2024-09-22 stays
+        assert!(without.contains(&"振替休日(秋分の日)".to_string()));
+        let csv = super::OutputBuilder::new(super::OutputFormat::CSV).include_equinoxes(false).build(2024).unwrap();
+        assert!(!csv.contains("春分の日,"));
+    }
+
+    #[test]
+    pub fn test_typical_month() {
+        use super::typical_month;
+        assert_eq!(typical_month("建国記念の日"), Some(2));
+        assert_eq!(typical_month("海の日"), Some(7));
+        assert_eq!(typical_month("春分の日"), Some(3));
+        assert_eq!(typical_month("秋分の日"), Some(9));
+        // The current rule, not the 12/23 of 1989-2018
+        assert_eq!(typical_month("天皇誕生日"), Some(2));
+        assert_eq!(typical_month("振替休日"), None);
+    }
+
+    #[test]
+    pub fn test_holidays_with_overrides() {
+        use super::{holidays_with_overrides, HolidayRule, RuleOverride};
+        let names_on = |items: &[super::HolidayShapedItem], date: &str| -> Vec<String> {
+            items.iter().filter(|h| h.date.to_string() == date).map(|h| h.name.clone()).collect()
+        };
+        // 海の日 on Sunday 2024-07-21 instead of Monday 7/15 gets a substitute the next day
+        let moved = RuleOverride::Rule { name: "海の日".to_string(), rule: HolidayRule::Fixed { month: 7, day: 21 } };
+        let items = holidays_with_overrides(2024, &[moved]).unwrap();
+        assert!(names_on(&items, "2024-07-15").is_empty());
+        assert_eq!(names_on(&items, "2024-07-21"), vec!["海の日"]);
+        assert_eq!(names_on(&items, "2024-07-22"), vec!["振替休日(海の日)"]);
+        assert_eq!(items.len(), 22);
+
+        let disabled = RuleOverride::Disable { name: "山の日".to_string() };
+        let items = holidays_with_overrides(2024, &[disabled]).unwrap();
+        // The substitute for 山の日 on Sunday 8/11 goes with it
+        assert!(items.iter().all(|h| !h.name.contains("山の日")));
+        assert_eq!(items.len(), 19);
+
+        assert_eq!(holidays_with_overrides(2024, &[]).unwrap().len(), 21);
+        assert!(holidays_with_overrides(2024, &[RuleOverride::Disable { name: "天皇誕生日".to_string() }]).is_ok());
+        assert!(holidays_with_overrides(2019, &[RuleOverride::Disable { name: "天皇誕生日".to_string() }]).is_err());
+    }
+
+    #[test]
+    pub fn test_long_weekends() {
+        use super::long_weekends;
+        let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+        let breaks = long_weekends(2024, 3).unwrap();
+        // Golden Week, 5/3 through the substitute for こどもの日 on 5/6
+        assert!(breaks.contains(&(date(5, 3), date(5, 6), 4)));
+        // 成人の日
+        assert!(breaks.contains(&(date(1, 6), date(1, 8), 3)));
+        assert!(breaks.iter().all(|(_, _, len)| *len >= 3));
+
+        assert_eq!(long_weekends(2024, 4).unwrap(), vec![(date(5, 3), date(5, 6), 4)]);
+        assert_eq!(long_weekends(2024, 1).unwrap().len(), long_weekends(2024, 0).unwrap().len());
+        assert!(long_weekends(2024, 5).unwrap().is_empty());
+    }
+
+    #[test]
+    pub fn test_cluster_index() {
+        let builder = super::OutputBuilder::new(super::OutputFormat::JSON).fields(super::Fields::CLUSTER_INDEX);
+        let items = builder.build_items(2024).unwrap();
+        let index = |date: &str| items.iter().find(|h| h.date.to_string() == date).unwrap().cluster_index;
+        // Golden Week, Friday 5/3 through the substitute on Monday 5/6
+        assert_eq!(index("2024-05-03"), Some((1, 4)));
+        assert_eq!(index("2024-05-04"), Some((2, 4)));
+        assert_eq!(index("2024-05-06"), Some((4, 4)));
+        // 勤労感謝の日 on Saturday with the Sunday after it
+        assert_eq!(index("2024-11-23"), Some((1, 2)));
+        // 春分の日 on Wednesday 2024-03-20 stands alone
+        assert_eq!(index("2024-03-20"), None);
+        assert!(items.iter().all(|h| h.long_weekend_length.is_none()));
+
+        let json: serde_json::Value = serde_json::from_str(&builder.build(2024).unwrap()).unwrap();
+        let golden_week = json.as_array().unwrap().iter().find(|h| h["date"] == "2024-05-04").unwrap();
+        assert_eq!(golden_week["cluster_index"], serde_json::json!([2, 4]));
+        assert!(!super::OutputBuilder::new(super::OutputFormat::JSON).build(2024).unwrap().contains("cluster_index"));
+    }
+
+    #[test]
+    pub fn test_holidays_range_json() {
+        let document: serde_json::Value = serde_json::from_str(&super::holidays_range_json(2023, 2025).unwrap()).unwrap();
+        let years = document.as_object().unwrap();
+        assert_eq!(years.keys().collect::<Vec<_>>(), vec!["2023", "2024", "2025"]);
+        for (year, holidays) in years {
+            let output = super::holiday(super::OutputFormat::JSON, year.parse::<u32>().unwrap()).unwrap();
+            assert_eq!(*holidays, serde_json::from_str::<serde_json::Value>(&output).unwrap());
+        }
+        assert_eq!(super::holidays_range_json(2025, 2024).unwrap(), "{}");
+    }
+
+    #[test]
+    pub fn test_new_year_on_sunday() {
+        use chrono::Datelike;
+        // Under both the single-Monday rule of 2006 and the rolling rule since 2007
+        for year in [2006, 2012, 2017, 2023] {
+            let days = super::holidays(year).unwrap();
+            let january: Vec<(u32, &str)> = days.iter()
+                .filter(|h| h.date.month() == 1)
+                .map(|h| (h.date.day(), h.name.as_str()))
+                .collect();
+            assert_eq!(january, vec![(1, "元旦"), (2, "振替休日(元旦)"), (9, "成人の日")], "{}", year);
+        }
+    }
+
+    #[test]
+    pub fn test_bilingual() {
+        let builder = super::OutputBuilder::new(super::OutputFormat::JSON).with_bilingual(true);
+        let items = builder.build_items(2024).unwrap();
+        assert_eq!(items[0].name, "元旦");
+        assert_eq!(items[0].name_en.as_deref(), Some("New Year's Day"));
+        let substitute = items.iter().find(|h| h.date.to_string() == "2024-05-06").unwrap();
+        assert_eq!(substitute.name_en.as_deref(), Some("Substitute Holiday (Children's Day)"));
+        // Named after the source with any SubstituteNaming
+        let plain = builder.substitute_naming(super::SubstituteNaming::Plain).build_items(2024).unwrap();
+        assert_eq!(plain.iter().find(|h| h.date.to_string() == "2024-05-06").unwrap().name_en, substitute.name_en);
+
+        for name in super::all_holiday_names() {
+            assert!(super::english_name(name).is_some(), "{} has no English name", name);
+        }
+        let default = super::OutputBuilder::new(super::OutputFormat::JSON).build_items(2024).unwrap();
+        assert!(default.iter().all(|h| h.name_en.is_none()));
+    }
+
+    #[test]
+    pub fn test_holiday_dates() {
+        // 2024 has substitutes for 建国記念の日, こどもの日, 山の日, 秋分の日 and 文化の日
+        let dates = super::holiday_dates(2024).unwrap();
+        assert_eq!(dates.len(), 21);
+        assert!(dates.windows(2).all(|w| w[0] < w[1]));
+        assert!(dates.contains(&NaiveDate::from_ymd_opt(2024, 5, 6).unwrap()));
+        assert_eq!(dates, super::holidays(2024).unwrap().iter().map(|h| h.date).collect::<Vec<_>>());
+    }
+
+    #[test]
+    pub fn test_same_date_holidays_merged() {
+        use super::{holidays_with_overrides, HolidayRule, RuleOverride};
+        // 海の日 moved onto 山の日, Sunday 2024-08-11
+        let moved = RuleOverride::Rule { name: "海の日".to_string(), rule: HolidayRule::Fixed { month: 8, day: 11 } };
+        let items = holidays_with_overrides(2024, &[moved]).unwrap();
+        let august: Vec<(String, String)> = items.iter()
+            .filter(|h| h.date.to_string().starts_with("2024-08"))
+            .map(|h| (h.date.to_string(), h.name.clone()))
+            .collect();
+        assert_eq!(august, vec![
+            ("2024-08-11".to_string(), "海の日・山の日".to_string()),
+            ("2024-08-12".to_string(), "振替休日(海の日・山の日)".to_string()),
+        ]);
+        assert_eq!(items.len(), 20);
+
+        let holiday = |date: &str| super::Holiday {
+            name: "振替休日".to_string(),
+            date: NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+            substitute: true,
+            category: super::HolidayCategory::Substitute,
+            source: Some("海の日・山の日".to_string()),
+            confidence: super::Confidence::Enacted,
+        };
+        assert_eq!(super::english_label(&holiday("2024-08-12")).as_deref(), Some("Substitute Holiday (Marine Day / Mountain Day)"));
+    }
+
+    #[test]
+    pub fn test_describe() {
+        assert_eq!(
+            super::describe(2024).unwrap(),
+            "2024年の祝日は21日間、うち振替休日5日、最長連休はゴールデンウィーク（5月3日〜5月6日の4日間）です。"
+        );
+        // Before 国民の休日 Golden Week could be a plain weekend, and the first three-day weekend wins
+        let summary = super::describe(1980).unwrap();
+        assert!(summary.contains("最長連休は2月9日〜2月11日の3日間です"), "{}", summary);
+    }
+
+    #[test]
+    pub fn test_holidays_range_iter() {
+        let mut years = super::holidays_range_iter(1980..2030);
+        let (first, days) = years.next().unwrap().unwrap();
+        assert_eq!(first, 1980);
+        assert_eq!(days, super::holidays(1980).unwrap());
+        let rest: Vec<(i32, Vec<super::Holiday>)> = years.map(Result::unwrap).collect();
+        assert_eq!(rest.len(), 49);
+        assert!(rest.iter().map(|(year, _)| *year).eq(1981..2030));
+        assert_eq!(rest[44], (2025, super::holidays(2025).unwrap()));
+        assert!(super::holidays_range_iter(2024..2024).next().is_none());
+    }
+
+    #[test]
+    pub fn test_apply_substitutes_to_csv() {
+        // 2024-06-02 and 2024-12-29 are Sundays
+        let csv = "date,name\n2024-06-02,創立記念日\n2024-06-04,社内行事\n2024-12-29,年末休暇\n2023-08-01,夏季休暇\n";
+        let result = super::apply_substitutes_to_csv(csv, 2024).unwrap();
+        assert_eq!(result, "date,name\n\
+            2024-06-02,創立記念日\n\
+            2024-06-03,振替休日(創立記念日)\n\
+            2024-06-04,社内行事\n\
+            2024-12-29,年末休暇\n\
+            2024-12-30,振替休日(年末休暇)\n");
+        assert!(super::apply_substitutes_to_csv("date,name\n2024-02-30,休暇\n", 2024).is_err());
+        assert!(super::apply_substitutes_to_csv("date,name\n2024-02-01\n", 2024).is_err());
+    }
+
+    #[test]
+    pub fn test_equinox_substitutes_match_general_pass() {
+        use chrono::{Datelike, Weekday};
+        // The dedicated equinox logic: a 春分の日 or 秋分の日 on Sunday moves to the next day that is neither a
+        // holiday nor a Sunday. The general substitute pass must place the same days.
+        let mut substituted = Vec::new();
+        for year in 2020..=2050 {
+            let days = super::holidays(year).unwrap();
+            let statutory: Vec<NaiveDate> = days.iter().filter(|h| !h.substitute).map(|h| h.date).collect();
+            let mut expected: Vec<NaiveDate> = Vec::new();
+            for e in days.iter().filter(|h| h.category == super::HolidayCategory::Equinox && h.date.weekday() == Weekday::Sun) {
+                let mut date = e.date.succ_opt().unwrap();
+                while statutory.contains(&date) || date.weekday() == Weekday::Sun {
+                    date = date.succ_opt().unwrap();
+                }
+                expected.push(date);
+                substituted.push(year);
+            }
+            let actual: Vec<NaiveDate> = days.iter()
+                .filter(|h| h.substitute && matches!(h.source.as_deref(), Some("春分の日" | "秋分の日")))
+                .map(|h| h.date)
+                .collect();
+            assert_eq!(actual, expected, "{}", year);
+        }
+        assert!(!substituted.is_empty());
+    }
+
+    #[test]
+    pub fn test_equinox_substitutes_once_and_sorted() {
+        use super::{OutputBuilder, OutputFormat};
+        let builder = OutputBuilder::new(OutputFormat::JSON);
+        for year in 2020..=2050 {
+            let days = super::holidays(year).unwrap();
+            assert!(days.windows(2).all(|w| w[0] <= w[1]), "{} is not sorted", year);
+            let items = builder.build_items(year).unwrap();
+            assert!(items.windows(2).all(|w| w[0].date <= w[1].date), "{} output is not sorted", year);
+            for source in ["春分の日", "秋分の日"] {
+                let label = format!("振替休日({})", source);
+                let from_days = days.iter().filter(|h| h.source.as_deref() == Some(source)).count();
+                let from_items = items.iter().filter(|h| h.name == label).count();
+                assert!(from_days <= 1, "{} of {} is substituted {} times", source, year, from_days);
+                assert_eq!(from_items, from_days, "{} in {}", label, year);
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_holidays_by_iso_week() {
+        let weeks = super::holidays_by_iso_week(2022).unwrap();
+        let names = |key: (i32, u32)| -> Vec<String> { weeks[&key].iter().map(|h| h.name.clone()).collect() };
+        // Saturday 2022-01-01 belongs to the last ISO week of 2021
+        assert_eq!(names((2021, 52)), vec!["元旦"]);
+        assert_eq!(names((2022, 2)), vec!["成人の日"]);
+        // Golden Week spans two ISO weeks
+        assert_eq!(names((2022, 17)), vec!["昭和の日"]);
+        assert_eq!(names((2022, 18)), vec!["憲法記念日", "みどりの日", "こどもの日"]);
+        assert_eq!(weeks.values().map(Vec::len).sum::<usize>(), super::holidays(2022).unwrap().len());
+        assert!(!weeks.contains_key(&(2022, 1)));
+    }
+
+    #[test]
+    pub fn test_days_until() {
+        use super::days_until;
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        // 海の日 is 2024-07-15
+        assert_eq!(days_until("海の日", date(2024, 7, 1)).unwrap(), Some(14));
+        assert_eq!(days_until("海の日", date(2024, 7, 15)).unwrap(), Some(0));
+        // Past for this year, so 2025-07-21
+        assert_eq!(days_until("海の日", date(2024, 7, 16)).unwrap(), Some(370));
+        // No 天皇誕生日 in 2019, and the one of 2018 has passed
+        assert_eq!(days_until("天皇誕生日", date(2018, 12, 24)).unwrap(), None);
+        assert_eq!(days_until("天皇誕生日", date(2019, 1, 1)).unwrap(), Some(418));
+        assert_eq!(days_until("存在しない日", date(2024, 1, 1)).unwrap(), None);
+        // 2100 is not searched
+        assert_eq!(days_until("元旦", date(2099, 12, 1)).unwrap(), None);
+        assert_eq!(days_until("秋分の日", date(1948, 1, 1)).unwrap(), Some(266));
+    }
+
+    #[test]
+    pub fn test_weekday_holidays_between() {
+        let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+        // Q4 2024: スポーツの日 on Monday, 文化の日 on Sunday with its substitute, 勤労感謝の日 on Saturday
+        let names: Vec<String> = super::weekday_holidays_between(date(10, 1), date(12, 31)).unwrap()
+            .into_iter()
+            .map(|h| h.name)
+            .collect();
+        assert_eq!(names, vec!["スポーツの日", "振替休日(文化の日)"]);
+        assert!(super::weekday_holidays_between(date(11, 23), date(11, 23)).unwrap().is_empty());
+    }
+
+    #[test]
+    pub fn test_era() {
+        let era = |y, m, d| super::era_name(NaiveDate::from_ymd_opt(y, m, d).unwrap());
+        assert_eq!(era(2024, 1, 1).as_deref(), Some("令和6年"));
+        // 昭和の日 2019 was under 平成, こどもの日 under 令和
+        assert_eq!(era(2019, 4, 29).as_deref(), Some("平成31年"));
+        assert_eq!(era(2019, 5, 5).as_deref(), Some("令和元年"));
+        assert_eq!(era(1989, 1, 1).as_deref(), Some("昭和64年"));
+        assert_eq!(era(1989, 1, 15).as_deref(), Some("平成元年"));
+        assert_eq!(era(1900, 1, 1), None);
+
+        let items = super::OutputBuilder::new(super::OutputFormat::JSON).with_era(true).build_items(2019).unwrap();
+        let eras: Vec<&str> = items.iter().filter_map(|h| h.era.as_deref()).collect();
+        assert_eq!(eras.len(), items.len());
+        assert_eq!(eras.first(), Some(&"平成31年"));
+        assert_eq!(eras.last(), Some(&"令和元年"));
+        assert!(super::OutputBuilder::new(super::OutputFormat::JSON).build_items(2019).unwrap().iter().all(|h| h.era.is_none()));
+    }
+
+    #[test]
+    pub fn test_holidays_affected_by() {
+        use super::holidays_affected_by;
+        assert_eq!(holidays_affected_by(2016).unwrap(), vec!["山の日"]);
+        assert_eq!(holidays_affected_by(2020).unwrap(), vec!["天皇誕生日", "体育の日", "スポーツの日"]);
+        assert_eq!(holidays_affected_by(2007).unwrap(), vec!["みどりの日", "昭和の日", "国民の休日", "振替休日"]);
+        assert_eq!(holidays_affected_by(1973).unwrap(), vec!["振替休日"]);
+        assert!(holidays_affected_by(2024).unwrap().is_empty());
+    }
+
+    #[test]
+    pub fn test_render_year_html() {
+        let html = super::render_year_html(2024).unwrap();
+        assert!(html.contains("<td class=\"holiday\" title=\"元旦\">1</td>"));
+        assert!(html.contains("<td class=\"holiday\" title=\"振替休日(建国記念の日)\">12</td>"));
+        assert!(html.contains("<td class=\"sun holiday\" title=\"建国記念の日\">11</td>"));
+        assert!(html.contains("<caption>2024年12月</caption>"));
+        assert_eq!(html.matches("title=").count(), 21);
+        assert_eq!(html.matches("<table class=\"month\">").count(), 12);
+        assert_eq!(super::escape_html("a<b>&\"c\""), "a&lt;b&gt;&amp;&quot;c&quot;");
+    }
+
+    #[test]
+    pub fn test_month_has_movable_holiday() {
+        use super::month_has_movable_holiday;
+        assert!(month_has_movable_holiday(2024, 1).unwrap());
+        assert!(!month_has_movable_holiday(2024, 5).unwrap());
+        assert!(month_has_movable_holiday(2024, 3).unwrap());
+        assert!(!month_has_movable_holiday(2024, 2).unwrap());
+        // 成人の日 was fixed to 1/15 until 1999
+        assert!(!month_has_movable_holiday(1999, 1).unwrap());
+        assert!(month_has_movable_holiday(-1, 1).is_err());
+    }
+
+    #[test]
+    pub fn test_csv_bom() {
+        use super::{OutputBuilder, OutputFormat};
+        let bytes = OutputBuilder::new(OutputFormat::CSV).with_bom(true).build_bytes(2024).unwrap();
+        assert_eq!(&bytes[..3], b"\xEF\xBB\xBF");
+        assert!(bytes[3..].starts_with(b"name,date,substitute"));
+        let plain = OutputBuilder::new(OutputFormat::CSV).build_bytes(2024).unwrap();
+        assert_eq!(&bytes[3..], &plain[..]);
+        let json = OutputBuilder::new(OutputFormat::JSON).with_bom(true).build_bytes(2024).unwrap();
+        assert_eq!(json[0], b'[');
+    }
+
+    #[test]
+    pub fn test_holidays_for_date_list() {
+        let dates = [
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2023, 1, 2).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(),
+            NaiveDate::from_ymd_opt(2023, 5, 4).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 2, 12).unwrap(),
+        ];
+        assert_eq!(super::holidays_for_date_list(&dates).unwrap(), vec![
+            Some("元旦".to_string()),
+            Some("振替休日(元旦)".to_string()),
+            None,
+            Some("みどりの日".to_string()),
+            Some("振替休日(建国記念の日)".to_string()),
+        ]);
+        assert!(super::holidays_for_date_list(&[]).unwrap().is_empty());
+        // Outside the supported years, as in `is_holiday`
+        for year in [-1, 1000, 1947, 2100] {
+            let date = NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
+            let err = super::holidays_for_date_list(&[dates[0], date]).unwrap_err();
+            assert!(matches!(err.downcast_ref::<super::HolidayError>(), Some(super::HolidayError::YearOutOfRange(y)) if *y == i64::from(year)));
+            let err = super::is_holiday(date).unwrap_err();
+            assert!(matches!(err.downcast_ref::<super::HolidayError>(), Some(super::HolidayError::YearOutOfRange(y)) if *y == i64::from(year)));
+        }
+    }
+
+    #[test]
+    pub fn test_one_off_holidays_2019() {
+        use chrono::Datelike;
+        use super::HolidayCategory::{Fixed, National, Special, Substitute};
+        let days = super::holidays(2019).unwrap();
+        let may: Vec<(String, String, super::HolidayCategory)> = days.iter()
+            .filter(|h| h.date.month() == 5 || h.date.month() == 4 && h.date.day() == 30)
+            .map(|h| (h.name.clone(), h.date.to_string(), h.category))
+            .collect();
+        assert_eq!(may, vec![
+            ("国民の休日".to_string(), "2019-04-30".to_string(), National),
+            ("即位の日".to_string(), "2019-05-01".to_string(), Special),
+            ("国民の休日".to_string(), "2019-05-02".to_string(), National),
+            ("憲法記念日".to_string(), "2019-05-03".to_string(), Fixed),
+            ("みどりの日".to_string(), "2019-05-04".to_string(), Fixed),
+            ("こどもの日".to_string(), "2019-05-05".to_string(), Fixed),
+            ("振替休日(こどもの日)".to_string(), "2019-05-06".to_string(), Substitute),
+        ]);
+        assert!(days.iter().any(|h| h.name == "即位礼正殿の儀" && h.date.to_string() == "2019-10-22"));
+        assert_eq!(days.len(), 22);
+        // The law of 2018 did not know them yet
+        assert!(super::holidays_as_of(2019, 2018).unwrap().iter().all(|h| h.name != "即位の日"));
+
+        for (year, name, date) in [(1959, "皇太子明仁親王の結婚の儀", "1959-04-10"), (1989, "昭和天皇の大喪の礼", "1989-02-24"),
+            (1990, "即位礼正殿の儀", "1990-11-12"), (1993, "皇太子徳仁親王の結婚の儀", "1993-06-09")] {
+            let days = super::holidays(year).unwrap();
+            assert!(days.iter().any(|h| h.name == name && h.date.to_string() == date && h.category == Special), "{} {}", year, name);
+        }
+    }
+
+    #[test]
+    pub fn test_unique_holidays_in_range() {
+        let unique: Vec<(String, String)> = super::unique_holidays_in_range(2019, 2015, 2025).unwrap()
+            .into_iter()
+            .map(|h| (h.name, h.date.to_string()))
+            .collect();
+        assert_eq!(unique, vec![
+            ("国民の休日".to_string(), "2019-04-30".to_string()),
+            ("即位の日".to_string(), "2019-05-01".to_string()),
+            ("国民の休日".to_string(), "2019-05-02".to_string()),
+            ("即位礼正殿の儀".to_string(), "2019-10-22".to_string()),
+        ]);
+        let olympics: Vec<String> = super::unique_holidays_in_range(2021, 2015, 2025).unwrap()
+            .into_iter()
+            .map(|h| h.name)
+            .collect();
+        assert_eq!(olympics, vec!["海の日", "スポーツの日", "山の日", "振替休日(山の日)"]);
+        assert!(super::unique_holidays_in_range(2024, 2024, 2024).unwrap().len() == 21);
+    }
+
+    #[test]
+    pub fn test_equinox_source() {
+        use super::{OutputBuilder, OutputFormat};
+        use super::super::timebase::{EquinoxSource, EQUINOX_OFFICIAL_UNTIL, VERNAL_EQUINOX_NAME};
+        let names = |source: EquinoxSource, year: u32| -> Vec<String> {
+            OutputBuilder::new(OutputFormat::JSON).equinox_source(source).build_items(year).unwrap()
+                .into_iter().map(|h| h.name).collect()
+        };
+        let inside = names(EquinoxSource::TableThenFormula, 2024);
+        assert_eq!(names(EquinoxSource::OfficialTable, 2024), inside);
+        assert_eq!(names(EquinoxSource::Formula, 2024), inside);
+        let year = EQUINOX_OFFICIAL_UNTIL + 3;
+        assert!(!names(EquinoxSource::OfficialTable, year).iter().any(|n| n == VERNAL_EQUINOX_NAME));
+        assert!(names(EquinoxSource::Formula, year).iter().any(|n| n == VERNAL_EQUINOX_NAME));
+        assert!(names(EquinoxSource::TableThenFormula, year).iter().any(|n| n == VERNAL_EQUINOX_NAME));
+    }
+
+    #[test]
+    pub fn test_previous_occurrence() {
+        use super::previous_occurrence;
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        // 海の日 of 2024 is still ahead in February, so the one of 2023
+        assert_eq!(previous_occurrence("海の日", date(2024, 2, 1)).unwrap(), Some(date(2023, 7, 17)));
+        assert_eq!(previous_occurrence("海の日", date(2024, 7, 16)).unwrap(), Some(date(2024, 7, 15)));
+        assert_eq!(previous_occurrence("海の日", date(2024, 7, 15)).unwrap(), Some(date(2023, 7, 17)));
+        assert_eq!(previous_occurrence("元旦", date(2024, 1, 2)).unwrap(), Some(date(2024, 1, 1)));
+        // No 天皇誕生日 in 2019
+        assert_eq!(previous_occurrence("天皇誕生日", date(2020, 1, 1)).unwrap(), None);
+        assert_eq!(previous_occurrence("存在しない日", date(2024, 1, 1)).unwrap(), None);
+        // 1947 is not searched
+        assert_eq!(previous_occurrence("秋分の日", date(1948, 9, 1)).unwrap(), None);
+        assert_eq!(previous_occurrence("元旦", date(2099, 12, 31)).unwrap(), Some(date(2099, 1, 1)));
+    }
+
+    #[test]
+    pub fn test_coverage() {
+        use super::super::rules::SubstituteMode;
+        use super::super::timebase::{EQUINOX_OFFICIAL_UNTIL, EQUINOX_RANGE};
+        use super::super::year::Year;
+        let coverage = super::coverage();
+        assert_eq!((coverage.min_year, coverage.max_year), (1948, EQUINOX_RANGE.1 as i32));
+        assert_eq!((coverage.min_year, coverage.max_year), (Year::MIN, Year::MAX));
+        assert_eq!(coverage.equinox_official_until, EQUINOX_OFFICIAL_UNTIL);
+        assert_eq!(coverage.substitute_rules_by_era, vec![
+            (1948, Some(1972), SubstituteMode::None),
+            (1973, Some(2006), SubstituteMode::SingleMonday),
+            (2007, None, SubstituteMode::Rolling),
+        ]);
+        // The 1973 era starts with the amendment in April
+        assert_eq!(coverage.substitute_since, NaiveDate::from_ymd_opt(1973, 4, 12).unwrap());
+    }
+
+    #[test]
+    pub fn test_days_off_lost_to_weekends() {
+        use super::days_off_lost_to_weekends;
+        // みどりの日 on May 4 and 勤労感謝の日 on November 23
+        assert_eq!(days_off_lost_to_weekends(2024).unwrap(), 2);
+        // Only 元旦; 2022-01-01 is a Saturday
+        assert_eq!(days_off_lost_to_weekends(2022).unwrap(), 1);
+        // 海の日 on July 20 and 勤労感謝の日; the 国民の休日 on Saturday 2002-05-04 is not counted
+        assert_eq!(days_off_lost_to_weekends(2002).unwrap(), 2);
+        assert!(days_off_lost_to_weekends(-1).is_err());
+    }
+
+    #[test]
+    pub fn test_time_unit() {
+        use super::{OutputBuilder, OutputFormat, TimeUnit};
+        let seconds = OutputBuilder::new(OutputFormat::JSON).with_time(true).build_items(2024).unwrap();
+        let millis = OutputBuilder::new(OutputFormat::JSON).with_time(true).time_unit(TimeUnit::Millis).build_items(2024).unwrap();
+        // 2024-01-01T00:00:00+09:00
+        assert_eq!(seconds[0].time, Some(1704034800));
+        assert_eq!(millis[0].time, Some(1704034800000));
+        for (s, m) in seconds.iter().zip(&millis) {
+            assert_eq!(m.time.unwrap(), s.time.unwrap() * 1000);
+        }
+        let epoch = super::holidays_epoch(2024).unwrap();
+        assert!(seconds.iter().zip(&epoch).all(|(s, (t, _))| s.time == Some(*t)));
+        assert!(OutputBuilder::new(OutputFormat::JSON).time_unit(TimeUnit::Millis).build_items(2024).unwrap()[0].time.is_none());
+        let csv = OutputBuilder::new(OutputFormat::CSV).with_time(true).time_unit(TimeUnit::Millis).build(2024).unwrap();
+        assert!(csv.starts_with("name,date,substitute,time\n元旦,2024-01-01,false,1704034800000\n"), "{}", csv);
+    }
+
+    #[test]
+    pub fn test_is_closed() {
+        use super::is_closed;
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        // Monday 2024-12-30 is a regular weekday apart from the closure
+        assert!(is_closed(date(2024, 12, 30), true).unwrap());
+        assert!(!is_closed(date(2024, 12, 30), false).unwrap());
+        assert!(is_closed(date(2025, 1, 3), true).unwrap());
+        assert!(!is_closed(date(2025, 1, 6), true).unwrap());
+        // A regular Wednesday, a Saturday and a holiday
+        assert!(!is_closed(date(2024, 6, 12), true).unwrap());
+        assert!(is_closed(date(2024, 6, 15), false).unwrap());
+        assert!(is_closed(date(2024, 7, 15), false).unwrap());
+    }
 }
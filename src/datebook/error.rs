@@ -0,0 +1,81 @@
+//! # Error
+//! Errors returned by the holiday lookups.
+//!
+//! `HolidayError` implements `std::error::Error`, so it converts into `anyhow::Error` with `?`.
+//! APIs returning `anyhow::Result` wrap it where it applies, and callers can recover it with
+//! `err.downcast_ref::<HolidayError>()`.
+use std::fmt;
+
+#[derive(Debug)]
+pub enum HolidayError {
+    // The input could not be parsed as a `%Y-%m-%d` date
+    InvalidDate(String),
+    // The year cannot be represented in the calendar computation
+    YearOutOfRange(i64),
+    // A relative holiday condition is not `month:n:weekday`, see `timebase::RelativeCondition`
+    InvalidCondition { condition: String, reason: String },
+    // The embedded holiday data could not be loaded
+    Data(anyhow::Error),
+}
+
+impl fmt::Display for HolidayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HolidayError::InvalidDate(input) => write!(f, "invalid date {:?}, expected YYYY-MM-DD", input),
+            HolidayError::YearOutOfRange(year) => write!(f, "year {} is out of range", year),
+            HolidayError::InvalidCondition { condition, reason } => write!(f, "invalid condition {:?}: {}", condition, reason),
+            HolidayError::Data(err) => write!(f, "failed to load holiday data: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for HolidayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HolidayError::Data(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+// A `HolidayError` passed up as `anyhow::Error` comes back as itself.
+impl From<anyhow::Error> for HolidayError {
+    fn from(err: anyhow::Error) -> Self {
+        match err.downcast::<HolidayError>() {
+            Ok(err) => err,
+            Err(err) => HolidayError::Data(err),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use pretty_assertions::assert_eq;
+    use std::error::Error;
+    use super::HolidayError;
+
+    #[test]
+    pub fn test_display() {
+        assert_eq!(HolidayError::InvalidDate("2024-13-01".to_string()).to_string(), "invalid date \"2024-13-01\", expected YYYY-MM-DD");
+        assert_eq!(HolidayError::YearOutOfRange(-1).to_string(), "year -1 is out of range");
+        let condition = HolidayError::InvalidCondition { condition: "july:6:monday".to_string(), reason: "n must be 1-5".to_string() };
+        assert_eq!(condition.to_string(), "invalid condition \"july:6:monday\": n must be 1-5");
+        let data = HolidayError::from(anyhow::anyhow!("bad row"));
+        assert_eq!(data.to_string(), "failed to load holiday data: bad row");
+        assert_eq!(data.source().map(|e| e.to_string()), Some("bad row".to_string()));
+        let wrapped = HolidayError::from(anyhow::Error::from(HolidayError::YearOutOfRange(1900)));
+        assert!(matches!(wrapped, HolidayError::YearOutOfRange(1900)));
+    }
+
+    #[test]
+    pub fn test_into_anyhow() {
+        fn lookup() -> anyhow::Result<Option<String>> {
+            Ok(super::super::calendar::holiday_status("2024-02-30")?)
+        }
+        let err = lookup().unwrap_err();
+        assert!(matches!(err.downcast_ref::<HolidayError>(), Some(HolidayError::InvalidDate(_))));
+
+        let err = super::super::calendar::holiday_bitmap(-1).unwrap_err();
+        assert!(matches!(err.downcast_ref::<HolidayError>(), Some(HolidayError::YearOutOfRange(-1))));
+    }
+}
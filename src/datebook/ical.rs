@@ -0,0 +1,250 @@
+//! # iCalendar
+//! The holidays as an iCalendar (RFC 5545) feed, for hosting a subscription URL.
+//!
+//! Each holiday is an all-day `VEVENT` whose `UID` is derived from its date and a stable hash of its
+//! name, e.g. `20240101-…@jpn-holiday-atlas`, so the events stay identical across regenerations.
+//! `write_ical_range_collapsed` writes each holiday fixed to the same date in every year of the range,
+//! such as 元旦, as one event repeating with `RRULE:FREQ=YEARLY`; Happy-Monday holidays, equinoxes,
+//! substitutes and holidays whose date changed within the range stay one event per year. The calendar carries `X-WR-CALNAME:日本の祝日` and asks subscribers
+//! to refresh weekly through `X-PUBLISHED-TTL` and `REFRESH-INTERVAL`.
+//!
+//! ```
+//! use jpn_holiday_atlas::datebook::ical::write_ical_range;
+//! let mut feed: Vec<u8> = Vec::new();
+//! write_ical_range(&mut feed, 2024, 2026).unwrap();
+//! assert!(String::from_utf8(feed).unwrap().starts_with("BEGIN:VCALENDAR\r\n"));
+//! ```
+use std::io::Write;
+use anyhow::{anyhow, Result};
+use chrono::Duration;
+use super::calendar::{holidays, Holiday, HolidayCategory};
+use super::timebase::StableHasher;
+use super::year::Year;
+
+pub const CALENDAR_NAME: &str = "日本の祝日";
+// How often subscribers should refresh, as an RFC 5545 duration
+pub const REFRESH_INTERVAL: &str = "P7D";
+const PRODUCT_ID: &str = "-//jpn_holiday_atlas//Japanese Holidays//JA";
+const UID_DOMAIN: &str = "jpn-holiday-atlas";
+
+/// Writes the holidays of `year` as one VCALENDAR.
+pub fn write_ical<W: Write>(writer: W, year: impl Into<Year>) -> Result<()> {
+    let year = year.into().to_u32()?;
+    write_ical_range(writer, year, year)
+}
+
+/// Writes the holidays of `start_year` through `end_year` (both inclusive) as one VCALENDAR.
+pub fn write_ical_range<W: Write>(writer: W, start_year: impl Into<Year>, end_year: impl Into<Year>) -> Result<()> {
+    write_feed(writer, start_year.into(), end_year.into(), false)
+}
+
+/// Same as [`write_ical_range`], with the holidays on the same date every year written once with a
+/// yearly `RRULE`, see the module docs.
+pub fn write_ical_range_collapsed<W: Write>(writer: W, start_year: impl Into<Year>, end_year: impl Into<Year>) -> Result<()> {
+    write_feed(writer, start_year.into(), end_year.into(), true)
+}
+
+fn write_feed<W: Write>(mut writer: W, start_year: Year, end_year: Year, collapse: bool) -> Result<()> {
+    let start_year = start_year.to_u32()?;
+    let end_year = end_year.to_u32()?;
+    if start_year > end_year {
+        return Err(anyhow!("start year {} is after end year {}", start_year, end_year));
+    }
+    let mut lines: Vec<String> = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODUCT_ID),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(CALENDAR_NAME)),
+        "X-WR-TIMEZONE:Asia/Tokyo".to_string(),
+        format!("X-PUBLISHED-TTL:{}", REFRESH_INTERVAL),
+        format!("REFRESH-INTERVAL;VALUE=DURATION:{}", REFRESH_INTERVAL),
+    ];
+    let years = (start_year..=end_year).map(holidays).collect::<Result<Vec<_>>>()?;
+    // The fixed holidays of the first year on the same month and day in every other year
+    let yearly: Vec<(String, String)> = if collapse && start_year < end_year {
+        years[0].iter()
+            .filter_map(fixed_key)
+            .filter(|key| years.iter().all(|days| days.iter().any(|h| fixed_key(h).as_ref() == Some(key))))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    for h in &years[0] {
+        if let Some((name, month_day)) = fixed_key(h).filter(|key| yearly.contains(key)) {
+            let uid = format!("yearly-{}-{:016x}", month_day, uid_hash(&name, &month_day));
+            push_event(&mut lines, h, &uid, Some(years.len()));
+        }
+    }
+    for h in years.iter().flatten() {
+        if fixed_key(h).is_some_and(|key| yearly.contains(&key)) {
+            continue;
+        }
+        let date = h.date.format("%Y%m%d").to_string();
+        let uid = format!("{}-{:016x}", date, uid_hash(&h.name, &date));
+        push_event(&mut lines, h, &uid, None);
+    }
+    lines.push("END:VCALENDAR".to_string());
+    for line in lines {
+        write!(writer, "{}\r\n", fold_line(&line))?;
+    }
+    Ok(())
+}
+
+// Name and `MMDD` of a holiday on the same month and day every year by the base data, as opposed to
+// a rule, a substitute or a one-off.
+fn fixed_key(holiday: &Holiday) -> Option<(String, String)> {
+    (holiday.category == HolidayCategory::Fixed && !holiday.substitute)
+        .then(|| (holiday.name.clone(), holiday.date.format("%m%d").to_string()))
+}
+
+// Appends the VEVENT of `holiday`, repeated yearly `count` times when given.
+fn push_event(lines: &mut Vec<String>, holiday: &Holiday, uid: &str, count: Option<usize>) {
+    let date = holiday.date.format("%Y%m%d");
+    lines.extend([
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}@{}", uid, UID_DOMAIN),
+        // Fixed rather than the time of writing, so an unchanged feed stays byte-identical
+        format!("DTSTAMP:{}T000000Z", date),
+        format!("DTSTART;VALUE=DATE:{}", date),
+        format!("DTEND;VALUE=DATE:{}", (holiday.date + Duration::days(1)).format("%Y%m%d")),
+    ]);
+    if let Some(count) = count {
+        lines.push(format!("RRULE:FREQ=YEARLY;COUNT={}", count));
+    }
+    lines.extend([
+        format!("SUMMARY:{}", escape_text(&holiday.name)),
+        "TRANSP:TRANSPARENT".to_string(),
+        "END:VEVENT".to_string(),
+    ]);
+}
+
+// Hash of the holiday name and its canonical date (`YYYYMMDD`) or yearly rule (`MMDD`), part of the UID.
+fn uid_hash(name: &str, when: &str) -> u64 {
+    let mut hasher = StableHasher::new();
+    hasher.write_part(name.as_bytes());
+    hasher.write_part(when.as_bytes());
+    hasher.finish()
+}
+
+// TEXT values escape backslashes, semicolons, commas and newlines.
+fn escape_text(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
+
+// Folds a content line longer than 75 octets, without splitting a UTF-8 character.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+pub mod test {
+    use pretty_assertions::assert_eq;
+    use std::collections::HashSet;
+
+    fn feed(start: u32, end: u32) -> String {
+        let mut bytes: Vec<u8> = Vec::new();
+        super::write_ical_range(&mut bytes, start, end).unwrap();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    pub fn test_subscription_headers() {
+        let ical = feed(2024, 2024);
+        let lines: Vec<&str> = ical.split("\r\n").collect();
+        assert_eq!(lines[0], "BEGIN:VCALENDAR");
+        assert!(lines.contains(&"X-WR-CALNAME:日本の祝日"));
+        assert!(lines.contains(&"X-PUBLISHED-TTL:P7D"));
+        assert!(lines.contains(&"REFRESH-INTERVAL;VALUE=DURATION:P7D"));
+        let first = lines.iter().position(|l| *l == "BEGIN:VEVENT").unwrap();
+        assert_eq!(lines[first..first + 8], [
+            "BEGIN:VEVENT",
+            &format!("UID:20240101-{:016x}@jpn-holiday-atlas", super::uid_hash("元旦", "20240101")),
+            "DTSTAMP:20240101T000000Z",
+            "DTSTART;VALUE=DATE:20240101",
+            "DTEND;VALUE=DATE:20240102",
+            "SUMMARY:元旦",
+            "TRANSP:TRANSPARENT",
+            "END:VEVENT",
+        ]);
+    }
+
+    #[test]
+    pub fn test_multi_year_feed() {
+        let ical = feed(2023, 2025);
+        assert!(ical.ends_with("END:VCALENDAR\r\n"));
+        let lines: Vec<&str> = ical.trim_end_matches("\r\n").split("\r\n").collect();
+        assert!(lines.iter().all(|l| !l.contains('\n') && l.len() <= 75));
+        assert_eq!(lines.iter().filter(|l| **l == "BEGIN:VCALENDAR").count(), 1);
+
+        let events = lines.iter().filter(|l| **l == "BEGIN:VEVENT").count();
+        assert_eq!(events, lines.iter().filter(|l| **l == "END:VEVENT").count());
+        let uids: HashSet<&str> = lines.iter().filter_map(|l| l.strip_prefix("UID:")).collect();
+        assert_eq!(uids.len(), events);
+        let expected: usize = (2023..=2025).map(|year| super::holidays(year).unwrap().len()).sum();
+        assert_eq!(events, expected);
+
+        let mut bytes: Vec<u8> = Vec::new();
+        assert!(super::write_ical_range(&mut bytes, 2025, 2023).is_err());
+    }
+
+    #[test]
+    pub fn test_fold_and_escape() {
+        assert_eq!(super::escape_text("a,b;c\\d"), "a\\,b\\;c\\\\d");
+        let long = format!("SUMMARY:{}", "祝".repeat(30));
+        let folded = super::fold_line(&long);
+        assert!(folded.split("\r\n").all(|l| l.len() <= 75), "{}", folded);
+        assert_eq!(folded.replace("\r\n ", ""), long);
+    }
+
+    #[test]
+    pub fn test_uid_stability() {
+        assert_eq!(feed(2024, 2026), feed(2024, 2026));
+        let uids = |ical: &str| -> Vec<String> {
+            ical.split("\r\n").filter_map(|l| l.strip_prefix("UID:")).map(str::to_string).collect()
+        };
+        // A holiday keeps its UID whatever range it is written in
+        let single = uids(&feed(2025, 2025));
+        let range = uids(&feed(2024, 2026));
+        assert!(single.iter().all(|uid| range.contains(uid)));
+        assert_eq!(super::uid_hash("元旦", "20240101"), super::uid_hash("元旦", "20240101"));
+        assert_ne!(super::uid_hash("元旦", "20240101"), super::uid_hash("元旦", "20250101"));
+    }
+
+    #[test]
+    pub fn test_collapsed_feed() {
+        let collapsed = |start: u32, end: u32| -> String {
+            let mut bytes: Vec<u8> = Vec::new();
+            super::write_ical_range_collapsed(&mut bytes, start, end).unwrap();
+            String::from_utf8(bytes).unwrap()
+        };
+        let ical = collapsed(2024, 2026);
+        assert_eq!(ical, collapsed(2024, 2026));
+        let lines: Vec<&str> = ical.split("\r\n").collect();
+        let first = lines.iter().position(|l| *l == "BEGIN:VEVENT").unwrap();
+        assert_eq!(lines[first + 1], format!("UID:yearly-0101-{:016x}@jpn-holiday-atlas", super::uid_hash("元旦", "0101")));
+        assert_eq!(lines[first + 5], "RRULE:FREQ=YEARLY;COUNT=3");
+        assert_eq!(lines[first + 6], "SUMMARY:元旦");
+        // 元旦, 建国記念の日, 天皇誕生日, 昭和の日, 憲法記念日, みどりの日, こどもの日, 山の日, 文化の日, 勤労感謝の日
+        assert_eq!(lines.iter().filter(|l| l.starts_with("RRULE:")).count(), 10);
+        assert_eq!(lines.iter().filter(|l| **l == "SUMMARY:元旦").count(), 1);
+        // Happy-Monday holidays stay one event per year
+        assert_eq!(lines.iter().filter(|l| **l == "SUMMARY:成人の日").count(), 3);
+        // 天皇誕生日 moved in 2020, so it is not collapsed across the change
+        let change = collapsed(2018, 2021);
+        assert!(!change.split("\r\n").any(|l| l.starts_with("UID:yearly-1223") || l.starts_with("UID:yearly-0223")));
+        // A single year has nothing to collapse
+        assert_eq!(collapsed(2024, 2024), feed(2024, 2024));
+    }
+}
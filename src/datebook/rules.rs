@@ -0,0 +1,616 @@
+//! # Rules
+//! Rules that derive additional holidays from the statutory ones.
+//!
+//! Each rule receives the holidays of a year sorted by date, appends what it derives
+//! and leaves the list sorted again, so rules can be applied one after another.
+//!
+//! | Rule | Description |
+//! | --- | --- |
+//! | SameDateMergeRule | two holidays on the same date become one entry named `A・B`, e.g. after a custom holiday or an override |
+//! | SundaySubstituteRule | 振替休日: a holiday on Sunday moves to the next day that is not a holiday |
+//! | NationalHolidayRule | 国民の休日: a day sandwiched between two holidays becomes a holiday |
+//!
+//! The substitute holiday rule changed over time, see `substitute_rule_for_year`:
+//!
+//! | Years | SubstituteMode | Substitute holiday |
+//! | --- | --- | --- |
+//! | until 1972 | None | none |
+//! | 1973-2006 | SingleMonday | the Monday after a holiday on Sunday, unless that day is already a holiday |
+//! | since 2007 | Rolling | the first day after a holiday on Sunday that is neither a holiday nor a Sunday |
+//!
+//! The boundaries are `SUBSTITUTE_SINCE` (1973) and `SUBSTITUTE_ROLLING_SINCE` (2007). The 1973 amendment
+//! took effect on `SUBSTITUTE_SINCE_DATE` (1973-04-12), so 建国記念の日 on Sunday 1973-02-11 has no substitute.
+//! 国民の休日 exists since `NATIONAL_HOLIDAY_SINCE` (1986); until 2006 a sandwiched Sunday stays a plain Sunday.
+//! Both rules follow the law of the year of the holidays, or of `law_year` when set, see `holidays_as_of`.
+//!
+//! `compute_substitutes` applies the same logic to any set of holidays, e.g. one including custom ones,
+//! and `compute_substitute_date` places the substitute of a single holiday among given dates.
+//! `SubstituteRules` combines the mode with the categories of holidays that get substitutes at all,
+//! all of them by default, and whether holidays on Saturday get one too. The law grants none for
+//! Saturdays, but some company calendars do; such a substitute also skips Saturdays.
+//!
+//! See: [Japanese national holiday law, Article 3](https://www8.cao.go.jp/chosei/shukujitsu/gaiyou.html)
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use super::calendar::{merge_sorted, Holiday, HolidayCategory};
+
+pub const SUBSTITUTE_HOLIDAY_NAME: &str = "振替休日";
+pub const NATIONAL_HOLIDAY_NAME: &str = "国民の休日";
+pub const SUBSTITUTE_HOLIDAY_LAW_REFERENCE: &str = "国民の祝日に関する法律 第3条第2項";
+pub const NATIONAL_HOLIDAY_LAW_REFERENCE: &str = "国民の祝日に関する法律 第3条第3項";
+// First year of 国民の休日, introduced by the amendment of December 1985
+pub const NATIONAL_HOLIDAY_SINCE: i32 = 1986;
+// First year of 振替休日, introduced by the amendment of April 1973
+pub const SUBSTITUTE_SINCE: i32 = 1973;
+// The day the 1973 amendment took effect; holidays before it in 1973 get no substitute
+pub const SUBSTITUTE_SINCE_DATE: NaiveDate = match NaiveDate::from_ymd_opt(SUBSTITUTE_SINCE, 4, 12) {
+    Some(date) => date,
+    None => panic!("invalid SUBSTITUTE_SINCE_DATE"),
+};
+// First year of the rolling substitute rule, and of 国民の休日 on a Sunday, from the amendment of 2005
+pub const SUBSTITUTE_ROLLING_SINCE: i32 = 2007;
+// Joins the names of holidays merged by `SameDateMergeRule`
+pub const MERGED_NAME_SEPARATOR: &str = "・";
+
+pub trait Rule {
+    fn apply(&self, holidays: &mut Vec<Holiday>);
+}
+
+// The rules applied to the national holidays, in order.
+pub fn default_rules() -> Vec<Box<dyn Rule>> {
+    rules_with(SubstituteNaming::default())
+}
+
+// The default rules, labelling substitute holidays with `naming`.
+pub fn rules_with(naming: SubstituteNaming) -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(SameDateMergeRule),
+        Box::new(SundaySubstituteRule { naming, ..Default::default() }),
+        Box::new(NationalHolidayRule::default()),
+    ]
+}
+
+// The default rules as the law stood in `law_year`, whatever the year of the holidays.
+pub fn rules_as_of(naming: SubstituteNaming, law_year: i32) -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(SameDateMergeRule),
+        Box::new(SundaySubstituteRule { naming, law_year: Some(law_year), ..Default::default() }),
+        Box::new(NationalHolidayRule { law_year: Some(law_year) }),
+    ]
+}
+
+// The year whose law applies to `data`: `law_year`, or else the year of the last holiday,
+// as earlier ones can be from the previous December.
+fn effective_law_year(law_year: Option<i32>, data: &[Holiday]) -> Option<i32> {
+    law_year.or_else(|| data.last().map(|h| h.date.year()))
+}
+
+// How substitute holidays are labelled.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum SubstituteNaming {
+    // `振替休日(こどもの日)`
+    #[default]
+    WithSource,
+    // `振替休日`
+    Plain,
+    // Built from the original holiday's name
+    Custom(fn(&str) -> String),
+}
+
+impl SubstituteNaming {
+    pub fn label(&self, source: &str) -> String {
+        match self {
+            SubstituteNaming::WithSource => format!("{}({})", SUBSTITUTE_HOLIDAY_NAME, source),
+            SubstituteNaming::Plain => SUBSTITUTE_HOLIDAY_NAME.to_string(),
+            SubstituteNaming::Custom(f) => f(source),
+        }
+    }
+}
+
+// Which substitute holiday rule is in force.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubstituteMode {
+    // Before the 1973 amendment there were no substitute holidays
+    None,
+    // 1973-2006: only the following Monday could become a substitute holiday
+    SingleMonday,
+    // Since 2007: the substitute rolls forward past any holidays
+    Rolling,
+}
+
+impl SubstituteMode {
+    pub fn for_year(year: i32) -> SubstituteMode {
+        if year < SUBSTITUTE_SINCE {
+            SubstituteMode::None
+        } else if year < SUBSTITUTE_ROLLING_SINCE {
+            SubstituteMode::SingleMonday
+        } else {
+            SubstituteMode::Rolling
+        }
+    }
+}
+
+/// The substitute holiday rule in force in `year`.
+pub fn substitute_rule_for_year(year: i32) -> SubstituteMode {
+    SubstituteMode::for_year(year)
+}
+
+// How substitute holidays are generated: the mode, and which categories of holidays on Sunday
+// get a substitute (`None` for all of them).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubstituteRules {
+    pub mode: SubstituteMode,
+    pub categories: Option<Vec<HolidayCategory>>,
+    // Holidays on Saturday get a substitute like those on Sunday, `false` as in the law
+    pub saturday_substitutes: bool,
+}
+
+impl SubstituteRules {
+    // The rule in force in `year`, for every category
+    pub fn for_year(year: i32) -> SubstituteRules {
+        SubstituteMode::for_year(year).into()
+    }
+
+    // Only holidays of `categories` get a substitute
+    pub fn only(mut self, categories: &[HolidayCategory]) -> SubstituteRules {
+        self.categories = Some(categories.to_vec());
+        self
+    }
+
+    // Holidays on Saturday get a substitute as well
+    pub fn with_saturday_substitutes(mut self, enabled: bool) -> SubstituteRules {
+        self.saturday_substitutes = enabled;
+        self
+    }
+
+    // Whether a holiday on `weekday` gets a substitute
+    fn substitutes_on(&self, weekday: Weekday) -> bool {
+        weekday == Weekday::Sun || (self.saturday_substitutes && weekday == Weekday::Sat)
+    }
+
+    pub fn applies_to(&self, category: HolidayCategory) -> bool {
+        self.categories.as_ref().is_none_or(|c| c.contains(&category))
+    }
+}
+
+impl From<SubstituteMode> for SubstituteRules {
+    fn from(mode: SubstituteMode) -> Self {
+        SubstituteRules { mode, categories: None, saturday_substitutes: false }
+    }
+}
+
+/// Substitute holidays generated for `holidays` under `year_rules`, sorted by date.
+///
+/// `holidays` may be in any order and is not modified; only the substitutes are returned.
+pub fn compute_substitutes(holidays: &[Holiday], year_rules: SubstituteRules) -> Vec<Holiday> {
+    substitutes(holidays, &year_rules, SubstituteNaming::default(), None)
+}
+
+// Holidays before `since`, when set, get no substitute.
+fn substitutes(holidays: &[Holiday], year_rules: &SubstituteRules, naming: SubstituteNaming, since: Option<NaiveDate>) -> Vec<Holiday> {
+    let mut added: Vec<Holiday> = Vec::new();
+    if year_rules.mode == SubstituteMode::None {
+        return added;
+    }
+    let mut data: Vec<&Holiday> = holidays.iter().filter(|h| since.is_none_or(|since| h.date >= since)).collect();
+    data.sort();
+    let mut i:usize = 0;
+    while i < data.len() {
+        // if it a Sunday, or a Saturday when those get substitutes too
+        let eligible = |h: &Holiday| year_rules.substitutes_on(h.date.weekday()) && year_rules.applies_to(h.category);
+        if eligible(data[i]) {
+            // The holiday on Sunday is the one being substituted, even when the run continues
+            let mut sources = vec![data[i]];
+            let run_end = match year_rules.mode {
+                SubstituteMode::Rolling => {
+                    // Skip the rest of the run of consecutive holidays, including entries sharing a date.
+                    // A later Sunday of the run, or a Saturday if those get substitutes, is substituted as well
+                    let mut last_holiday_date = data[i].date;
+                    while let Some(next_holiday) = data.get(i+1) {
+                        if next_holiday.date > last_holiday_date + Duration::days(1) {
+                            break;
+                        }
+                        i += 1;
+                        if next_holiday.date != last_holiday_date && eligible(next_holiday) {
+                            sources.push(next_holiday);
+                        }
+                        last_holiday_date = next_holiday.date;
+                    }
+                    Some(last_holiday_date)
+                }
+                _ => None,
+            };
+            for source in sources {
+                let sub_date = match run_end {
+                    Some(last_holiday_date) => Some(first_open_day_after(last_holiday_date, |date| {
+                        data.iter().any(|h| h.date == date)
+                            || added.iter().any(|h| h.date == date)
+                            || (year_rules.saturday_substitutes && date.weekday() == Weekday::Sat)
+                    })),
+                    None => {
+                        // The following Monday
+                        let next_day = source.date + Duration::days(7 - i64::from(source.date.weekday().num_days_from_monday()));
+                        if added.iter().any(|h| h.date == next_day) {
+                            None
+                        } else if data.iter().any(|h| h.date == next_day) {
+                            trace_step!(date = %next_day, "the following Monday is already a holiday, no substitute");
+                            None
+                        } else {
+                            Some(next_day)
+                        }
+                    }
+                };
+                if let Some(sub_date) = sub_date {
+                    trace_step!(source = %source.name, date = %sub_date, "substitute holiday added");
+                    added.push(Holiday {
+                        name: naming.label(&source.name),
+                        date: sub_date,
+                        substitute: true,
+                        category: HolidayCategory::Substitute,
+                        source: Some(source.name.clone()),
+                        confidence: source.confidence,
+                    });
+                }
+            }
+        } else {
+            trace_step!(name = %data[i].name, date = %data[i].date, "not a Sunday or not substituted, no substitute");
+        }
+        i += 1;
+    }
+    added
+}
+
+/// Where the substitute for a holiday on `holiday_date` lands under the current rule, given the dates
+/// of the other holidays, or `None` if `holiday_date` is not a Sunday.
+///
+/// The substitute is the first day after `holiday_date` that is neither in `existing` nor a Sunday,
+/// as in [`SubstituteMode::Rolling`]. Substitutes already placed belong in `existing` too.
+pub fn compute_substitute_date(holiday_date: NaiveDate, existing: &[NaiveDate]) -> Option<NaiveDate> {
+    if holiday_date.weekday() != Weekday::Sun {
+        return None;
+    }
+    Some(first_open_day_after(holiday_date, |date| existing.contains(&date)))
+}
+
+// The first day after `date` that is neither a holiday nor a Sunday. A Sunday would not add a day off,
+// so a substitute moves past it too.
+fn first_open_day_after(date: NaiveDate, is_holiday: impl Fn(NaiveDate) -> bool) -> NaiveDate {
+    let mut day = date + Duration::days(1);
+    while is_holiday(day) || day.weekday() == Weekday::Sun {
+        trace_step!(date = %day, "substitute candidate is already a holiday or a Sunday");
+        day += Duration::days(1);
+    }
+    day
+}
+
+// Merges statutory holidays sharing a date into the first of them, named e.g. `海の日・山の日` in the
+// order listed, with the weakest confidence of the two. Applied first, so the date gets one substitute.
+// Substitutes and 国民の休日 are left alone; an exact duplicate is dropped.
+#[derive(Debug, Default)]
+pub struct SameDateMergeRule;
+
+impl Rule for SameDateMergeRule {
+    fn apply(&self, data: &mut Vec<Holiday>) {
+        let statutory = |h: &Holiday| !h.substitute && h.category != HolidayCategory::National;
+        let mut merged: Vec<Holiday> = Vec::with_capacity(data.len());
+        for h in std::mem::take(data) {
+            match merged.last_mut() {
+                Some(last) if last.date == h.date && statutory(last) && statutory(&h) => {
+                    if !last.name.split(MERGED_NAME_SEPARATOR).any(|name| name == h.name) {
+                        trace_step!(date = %h.date, first = %last.name, second = %h.name, "holidays on the same date merged");
+                        last.name = format!("{}{}{}", last.name, MERGED_NAME_SEPARATOR, h.name);
+                    }
+                    last.confidence = last.confidence.max(h.confidence);
+                }
+                _ => merged.push(h),
+            }
+        }
+        *data = merged;
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SundaySubstituteRule {
+    pub naming: SubstituteNaming,
+    // Categories that get a substitute, see `SubstituteRules::categories`
+    pub categories: Option<Vec<HolidayCategory>>,
+    // The year whose substitute rules apply, the year of the holidays when `None`
+    pub law_year: Option<i32>,
+    // see `SubstituteRules::saturday_substitutes`
+    pub saturday_substitutes: bool,
+}
+
+impl Rule for SundaySubstituteRule {
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", name = "substitute_adjustment", skip_all))]
+    fn apply(&self, data: &mut Vec<Holiday>) {
+        let year_rules = match effective_law_year(self.law_year, data) {
+            Some(year) => SubstituteRules {
+                mode: SubstituteMode::for_year(year),
+                categories: self.categories.clone(),
+                saturday_substitutes: self.saturday_substitutes,
+            },
+            None => return,
+        };
+        // The law of the holidays' own year took effect on a date; `law_year` stands for the law as amended
+        let since = self.law_year.is_none().then_some(SUBSTITUTE_SINCE_DATE);
+        let added = substitutes(data, &year_rules, self.naming, since);
+        *data = merge_sorted(std::mem::take(data), added);
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct NationalHolidayRule {
+    // The year whose law applies, the year of the holidays when `None`
+    pub law_year: Option<i32>,
+}
+
+impl Rule for NationalHolidayRule {
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", name = "national_holiday", skip_all))]
+    fn apply(&self, data: &mut Vec<Holiday>) {
+        let law_year = match effective_law_year(self.law_year, data) {
+            Some(year) if year >= NATIONAL_HOLIDAY_SINCE => year,
+            _ => return,
+        };
+        let statutory: Vec<&Holiday> = data.iter().filter(|h| !h.substitute).collect();
+        let mut added: Vec<Holiday> = Vec::new();
+        for pair in statutory.windows(2) {
+            if pair[1].date - pair[0].date != Duration::days(2) {
+                continue;
+            }
+            let date = pair[0].date + Duration::days(1);
+            if data.iter().any(|h| h.date == date) {
+                trace_step!(date = %date, "sandwiched day is already a holiday");
+                continue;
+            }
+            if law_year < SUBSTITUTE_ROLLING_SINCE && date.weekday() == Weekday::Sun {
+                trace_step!(date = %date, "sandwiched Sunday is not a national holiday before 2007");
+                continue;
+            }
+            trace_step!(date = %date, before = %pair[0].name, after = %pair[1].name, "national holiday added");
+            added.push(Holiday {
+                name: NATIONAL_HOLIDAY_NAME.to_string(),
+                date,
+                substitute: false,
+                category: HolidayCategory::National,
+                source: None,
+                confidence: pair[0].confidence.max(pair[1].confidence),
+            });
+        }
+        *data = merge_sorted(std::mem::take(data), added);
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use pretty_assertions::assert_eq;
+    use chrono::NaiveDate;
+    use super::{Rule, SundaySubstituteRule, NationalHolidayRule, SubstituteNaming, SubstituteMode, SubstituteRules, compute_substitutes, compute_substitute_date, SameDateMergeRule};
+    use super::{substitute_rule_for_year, SUBSTITUTE_SINCE, SUBSTITUTE_ROLLING_SINCE};
+    use super::super::calendar::HolidayCategory::{Equinox, Fixed};
+    use super::super::calendar::{Confidence, Holiday, HolidayCategory};
+
+    fn holiday(name: &str, y: i32, m: u32, d: u32) -> Holiday {
+        Holiday {
+            name: name.to_string(),
+            date: NaiveDate::from_ymd_opt(y, m, d).unwrap(),
+            substitute: false,
+            category: HolidayCategory::Fixed,
+            source: None,
+            confidence: Confidence::Enacted,
+        }
+    }
+
+    fn summary(data: &[Holiday]) -> Vec<(String, String, bool)> {
+        data.iter().map(|h| (h.name.clone(), h.date.to_string(), h.substitute)).collect()
+    }
+
+    #[test]
+    pub fn test_sunday_substitute_rule() {
+        // 2023-01-01 is a Sunday
+        let mut data = vec![holiday("元旦", 2023, 1, 1), holiday("成人の日", 2023, 1, 9)];
+        SundaySubstituteRule::default().apply(&mut data);
+        assert_eq!(summary(&data), vec![
+            ("元旦".to_string(), "2023-01-01".to_string(), false),
+            ("振替休日(元旦)".to_string(), "2023-01-02".to_string(), true),
+            ("成人の日".to_string(), "2023-01-09".to_string(), false),
+        ]);
+    }
+
+    #[test]
+    pub fn test_substitute_naming() {
+        assert_eq!(SubstituteNaming::WithSource.label("元旦"), "振替休日(元旦)");
+        assert_eq!(SubstituteNaming::Plain.label("元旦"), "振替休日");
+        let english = SubstituteNaming::Custom(|source| format!("Substitute Holiday for {}", source));
+        let mut data = vec![holiday("元旦", 2023, 1, 1)];
+        SundaySubstituteRule { naming: english, ..Default::default() }.apply(&mut data);
+        assert_eq!(data[1].name, "Substitute Holiday for 元旦");
+    }
+
+    #[test]
+    pub fn test_sunday_substitute_rule_ignores_weekdays() {
+        let mut data = vec![holiday("元旦", 2024, 1, 1)];
+        SundaySubstituteRule::default().apply(&mut data);
+        assert_eq!(data.len(), 1);
+    }
+
+    #[test]
+    pub fn test_national_holiday_rule() {
+        // 2026: 敬老の日 on Sep 21 and 秋分の日 on Sep 23
+        let mut data = vec![holiday("敬老の日", 2026, 9, 21), holiday("秋分の日", 2026, 9, 23)];
+        NationalHolidayRule::default().apply(&mut data);
+        assert_eq!(summary(&data), vec![
+            ("敬老の日".to_string(), "2026-09-21".to_string(), false),
+            ("国民の休日".to_string(), "2026-09-22".to_string(), false),
+            ("秋分の日".to_string(), "2026-09-23".to_string(), false),
+        ]);
+    }
+
+    #[test]
+    pub fn test_national_holiday_rule_skips_existing_holiday() {
+        let mut data = vec![
+            holiday("憲法記念日", 2024, 5, 3),
+            holiday("みどりの日", 2024, 5, 4),
+            holiday("こどもの日", 2024, 5, 5),
+        ];
+        NationalHolidayRule::default().apply(&mut data);
+        assert_eq!(data.len(), 3);
+    }
+
+    #[test]
+    pub fn test_substitute_rules_for_year() {
+        assert_eq!(SubstituteMode::for_year(1972), SubstituteMode::None);
+        assert_eq!(SubstituteMode::for_year(1973), SubstituteMode::SingleMonday);
+        assert_eq!(SubstituteMode::for_year(2006), SubstituteMode::SingleMonday);
+        assert_eq!(SubstituteMode::for_year(2007), SubstituteMode::Rolling);
+        assert_eq!(substitute_rule_for_year(SUBSTITUTE_SINCE - 1), SubstituteMode::None);
+        assert_eq!(substitute_rule_for_year(SUBSTITUTE_SINCE), SubstituteMode::SingleMonday);
+        assert_eq!(substitute_rule_for_year(SUBSTITUTE_ROLLING_SINCE - 1), SubstituteMode::SingleMonday);
+        assert_eq!(substitute_rule_for_year(SUBSTITUTE_ROLLING_SINCE), SubstituteMode::Rolling);
+        assert_eq!(SubstituteRules::for_year(2007), SubstituteRules { mode: SubstituteMode::Rolling, categories: None, saturday_substitutes: false });
+    }
+
+    #[test]
+    pub fn test_compute_substitutes() {
+        // 2026-05-03 is a Sunday, followed by みどりの日 and こどもの日; given out of order
+        let data = vec![
+            holiday("こどもの日", 2026, 5, 5),
+            holiday("憲法記念日", 2026, 5, 3),
+            holiday("みどりの日", 2026, 5, 4),
+        ];
+        assert_eq!(summary(&compute_substitutes(&data, SubstituteMode::Rolling.into())), vec![
+            ("振替休日(憲法記念日)".to_string(), "2026-05-06".to_string(), true),
+        ]);
+        // Before 2007 the Monday was already a holiday, so there was no substitute
+        assert_eq!(compute_substitutes(&data, SubstituteMode::SingleMonday.into()).len(), 0);
+        assert_eq!(compute_substitutes(&data, SubstituteMode::None.into()).len(), 0);
+
+        // A custom holiday on Sunday gets a substitute like any other
+        let custom = vec![holiday("創立記念日", 2024, 6, 2)];
+        assert_eq!(summary(&compute_substitutes(&custom, SubstituteMode::SingleMonday.into())), vec![
+            ("振替休日(創立記念日)".to_string(), "2024-06-03".to_string(), true),
+        ]);
+        assert_eq!(custom.len(), 1);
+    }
+
+    #[test]
+    pub fn test_sunday_substitute_rule_consecutive_run() {
+        // Sunday through Tuesday, then a holiday on Thursday
+        let mut data = vec![
+            holiday("A", 2026, 5, 3),
+            holiday("B", 2026, 5, 4),
+            holiday("C", 2026, 5, 5),
+            holiday("D", 2026, 5, 7),
+        ];
+        SundaySubstituteRule::default().apply(&mut data);
+        let substitutes: Vec<_> = summary(&data).into_iter().filter(|h| h.2).collect();
+        assert_eq!(substitutes, vec![("振替休日(A)".to_string(), "2026-05-06".to_string(), true)]);
+
+        // The run continues through the first free weekday candidate
+        let mut data = vec![
+            holiday("A", 2026, 5, 3),
+            holiday("B", 2026, 5, 4),
+            holiday("C", 2026, 5, 5),
+            holiday("D", 2026, 5, 6),
+        ];
+        SundaySubstituteRule::default().apply(&mut data);
+        let substitutes: Vec<_> = summary(&data).into_iter().filter(|h| h.2).collect();
+        assert_eq!(substitutes, vec![("振替休日(A)".to_string(), "2026-05-07".to_string(), true)]);
+
+        // A run starting on Saturday is substituted once, for its Sunday
+        let mut data = vec![holiday("A", 2026, 5, 2), holiday("B", 2026, 5, 3), holiday("C", 2026, 5, 4)];
+        SundaySubstituteRule::default().apply(&mut data);
+        let substitutes: Vec<_> = summary(&data).into_iter().filter(|h| h.2).collect();
+        assert_eq!(substitutes, vec![("振替休日(B)".to_string(), "2026-05-05".to_string(), true)]);
+    }
+
+    #[test]
+    pub fn test_compute_substitutes_shared_date() {
+        // Two sources with a holiday on the same Sunday yield a single substitute
+        let data = vec![holiday("創立記念日", 2024, 6, 2), holiday("地域の祭日", 2024, 6, 2)];
+        assert_eq!(compute_substitutes(&data, SubstituteMode::Rolling.into()).len(), 1);
+        assert_eq!(compute_substitutes(&data, SubstituteMode::SingleMonday.into()).len(), 1);
+    }
+
+    #[test]
+    pub fn test_substitute_rules_categories() {
+        // 2024-09-22 (秋分の日) and 2024-11-03 (文化の日) are Sundays
+        let mut data = vec![holiday("秋分の日", 2024, 9, 22), holiday("文化の日", 2024, 11, 3)];
+        data[0].category = Equinox;
+        let all = SubstituteRules::from(SubstituteMode::Rolling);
+        assert!(all.applies_to(Equinox));
+        assert_eq!(compute_substitutes(&data, all.clone()).len(), 2);
+
+        let fixed_only = all.only(&[Fixed]);
+        assert!(!fixed_only.applies_to(Equinox));
+        assert_eq!(summary(&compute_substitutes(&data, fixed_only)), vec![
+            ("振替休日(文化の日)".to_string(), "2024-11-04".to_string(), true),
+        ]);
+
+        let mut rule_data = data;
+        SundaySubstituteRule { categories: Some(vec![Equinox]), ..Default::default() }.apply(&mut rule_data);
+        let substitutes: Vec<_> = summary(&rule_data).into_iter().filter(|h| h.2).collect();
+        assert_eq!(substitutes, vec![("振替休日(秋分の日)".to_string(), "2024-09-23".to_string(), true)]);
+    }
+
+    #[test]
+    pub fn test_substitute_skips_sunday_after_long_run() {
+        // Custom holidays from Sunday 2024-09-01 through Saturday 2024-09-07: Sunday 09-08 is skipped
+        let mut data: Vec<Holiday> = (1..=7).map(|d| holiday(&format!("休日{}", d), 2024, 9, d)).collect();
+        SundaySubstituteRule::default().apply(&mut data);
+        let substitutes: Vec<(String, String, bool)> = summary(&data).into_iter().filter(|h| h.2).collect();
+        assert_eq!(substitutes, vec![("振替休日(休日1)".to_string(), "2024-09-09".to_string(), true)]);
+    }
+
+    #[test]
+    pub fn test_compute_substitute_date() {
+        let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+        // Not a Sunday
+        assert_eq!(compute_substitute_date(date(5, 3), &[]), None);
+        // こどもの日 on Sunday 2024-05-05
+        assert_eq!(compute_substitute_date(date(5, 5), &[date(5, 3), date(5, 4)]), Some(date(5, 6)));
+        // Rolls past the holidays after it
+        assert_eq!(compute_substitute_date(date(5, 5), &[date(5, 6), date(5, 7)]), Some(date(5, 8)));
+        // and past a Sunday ending the run
+        let week: Vec<NaiveDate> = (6..=11).map(|d| date(5, d)).collect();
+        assert_eq!(compute_substitute_date(date(5, 5), &week), Some(date(5, 13)));
+        // Its own date in `existing` is ignored
+        assert_eq!(compute_substitute_date(date(5, 5), &[date(5, 5)]), Some(date(5, 6)));
+    }
+
+    #[test]
+    pub fn test_same_date_merge() {
+        // A custom holiday on 山の日, Sunday 2024-08-11
+        let mut data = vec![
+            holiday("山の日", 2024, 8, 11),
+            holiday("創立記念日", 2024, 8, 11),
+            holiday("山の日", 2024, 8, 11),
+        ];
+        data.sort();
+        SameDateMergeRule.apply(&mut data);
+        assert_eq!(data.len(), 1);
+        assert_eq!(data[0].name, "創立記念日・山の日");
+
+        SundaySubstituteRule::default().apply(&mut data);
+        assert_eq!(data[1].name, "振替休日(創立記念日・山の日)");
+        assert_eq!(data[1].date, NaiveDate::from_ymd_opt(2024, 8, 12).unwrap());
+        // The substitute is not merged again
+        SameDateMergeRule.apply(&mut data);
+        assert_eq!(data.len(), 2);
+    }
+
+    #[test]
+    pub fn test_saturday_substitutes() {
+        // 勤労感謝の日 on Saturday 2024-11-23, and a Sunday holiday right after another Saturday one
+        let data = vec![holiday("勤労感謝の日", 2024, 11, 23), holiday("土曜休暇", 2024, 12, 28), holiday("日曜休暇", 2024, 12, 29)];
+        assert_eq!(compute_substitutes(&data, SubstituteMode::Rolling.into()).len(), 1);
+
+        let rules = SubstituteRules::for_year(2024).with_saturday_substitutes(true);
+        assert_eq!(summary(&compute_substitutes(&data, rules)), vec![
+            ("振替休日(勤労感謝の日)".to_string(), "2024-11-25".to_string(), true),
+            ("振替休日(土曜休暇)".to_string(), "2024-12-30".to_string(), true),
+            ("振替休日(日曜休暇)".to_string(), "2024-12-31".to_string(), true),
+        ]);
+        // The single-Monday rule moves it to the Monday after as well
+        let rules = SubstituteRules::from(SubstituteMode::SingleMonday).with_saturday_substitutes(true);
+        assert_eq!(compute_substitutes(&data[..1], rules)[0].date, NaiveDate::from_ymd_opt(2024, 11, 25).unwrap());
+    }
+}
@@ -10,79 +10,188 @@
 //! See: [Japanese national holiday law](https://www8.cao.go.jp/chosei/shukujitsu/gaiyou.html)
 //!
 //! The vernal and autumnal equinoxes are not strictly calculated, as they are affected by the actual astronomical motion of the celestial bodies.
-//! Currently, the projected dates from 2020 to 2050 are returned.
+//! The equinox table is generated at build time by `build.rs` from the approximation formula (1900-2099 by default,
+//! configurable with `JPN_HOLIDAY_ATLAS_EQUINOX_RANGE=START-END`), with the projected dates from 2020 to 2050
+//! in `equinox_base_dates.csv` applied as overrides.
 //! See: [Vernal Equinox Day](https://ja.wikipedia.org/wiki/%E6%98%A5%E5%88%86%E3%81%AE%E6%97%A5)
 //!
+//! Holidays held in one year only, such as 即位の日 in 2019, are listed in `one_off_holidays.csv`.
+//!
+//! `build.rs` also validates `base.csv`, `special_holidays.csv` and `one_off_holidays.csv` (columns, dates, conditions, `since`/`until`
+//! periods and names), so the embedded data always parses; see the list there.
+//!
 //! ## Usage
-//!    use datebook;
-//!    use datebook::timebase::defaults;
 //! ```
-//!  fn main() {
-//!      let d = defaults().unwrap();
-//!     println!("{:?}", d);
-//!  }
+//! use jpn_holiday_atlas::datebook::timebase::get_schedule;
+//! let d = get_schedule().unwrap();
+//! println!("{:?}", d);
 //! ```
 
 use csv;
+use std::sync::OnceLock;
+use std::str::FromStr;
+use chrono::Weekday;
+use super::error::HolidayError;
+use super::year::Year;
 #[allow(unused_imports)]
-use anyhow::{Result, Error};
+use anyhow::{anyhow, Result, Error};
 const BASE_DATA: &[u8] = include_bytes!("../resources/base.csv");
-const BASE_EQUINOX: &[u8] = include_bytes!("../resources/equinox_base_dates.csv");
+const SPECIAL_DATA: &[u8] = include_bytes!("../resources/special_holidays.csv");
+const ONE_OFF_DATA: &[u8] = include_bytes!("../resources/one_off_holidays.csv");
+
+// EQUINOX_RANGE, EQUINOX_FORMULA and EQUINOX_TABLE, generated by build.rs
+#[allow(dead_code)]
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/equinox_table.rs"));
+}
+#[allow(unused_imports)]
+pub use generated::{EQUINOX_FORMULA, EQUINOX_RANGE, EQUINOX_TABLE};
+pub const VERNAL_EQUINOX_NAME: &str = "春分の日";
+pub const AUTUMNAL_EQUINOX_NAME: &str = "秋分の日";
+// First years the equinox holidays are observed; the act came into force on 1948-07-20
+pub const VERNAL_EQUINOX_SINCE: u32 = 1949;
+pub const AUTUMNAL_EQUINOX_SINCE: u32 = 1948;
+// Last year whose equinox days are officially announced; the National Astronomical Observatory
+// publishes the next year's dates every February. Later years come from the approximation formula.
+pub const EQUINOX_OFFICIAL_UNTIL: u32 = 2027;
+// The equinox days are not in the base data but established by the same article
+pub const EQUINOX_LAW_REFERENCE: &str = "国民の祝日に関する法律 第2条";
+
+// The date of a relative holiday, the `nth` `weekday` of `month`, written `month:n:weekday` in
+// `base.csv`, e.g. `january:2:monday`. Month and weekday names are English, full or abbreviated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RelativeCondition {
+    pub month: u32,
+    pub nth: u32,
+    pub weekday: Weekday,
+}
 
-#[derive(Debug)]
-pub struct Condition {
-    pub month: String,
-    pub n: u32,
-    pub weekday: String,
+impl FromStr for RelativeCondition {
+    type Err = HolidayError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: String| HolidayError::InvalidCondition { condition: s.to_string(), reason };
+        let parts: Vec<&str> = s.split(':').map(str::trim).collect();
+        let [month, nth, weekday] = parts.as_slice() else {
+            return Err(invalid(format!("expected month:n:weekday, but got {} components", parts.len())));
+        };
+        let month = month_from_name(month).ok_or_else(|| invalid(format!("unknown month {:?}", month)))?;
+        let nth = match nth.parse::<u32>() {
+            Ok(n) if (1..=5).contains(&n) => n,
+            _ => return Err(invalid(format!("n must be 1-5, but got {:?}", nth))),
+        };
+        let weekday = weekday_from_name(weekday).ok_or_else(|| invalid(format!("unknown weekday {:?}", weekday)))?;
+        Ok(RelativeCondition { month, nth, weekday })
+    }
 }
 
-#[derive(Debug)]
+fn weekday_from_name(name: &str)-> Option<Weekday> {
+    match name.trim().to_lowercase().as_str() {
+        "monday" | "mon" => Some(Weekday::Mon),
+        "tuesday" | "tue" => Some(Weekday::Tue),
+        "wednesday" | "wed" => Some(Weekday::Wed),
+        "thursday" | "thu" => Some(Weekday::Thu),
+        "friday" | "fri" => Some(Weekday::Fri),
+        "saturday" | "sat" => Some(Weekday::Sat),
+        "sunday" | "sun" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn month_from_name(name: &str) -> Option<u32> {
+    match name.trim().to_lowercase().as_str() {
+        "january" | "jan" => Some(1),
+        "february" | "feb" => Some(2),
+        "march" | "mar" => Some(3),
+        "april" | "apr" => Some(4),
+        "may" => Some(5),
+        "june" | "jun" => Some(6),
+        "july" | "jul" => Some(7),
+        "august" | "aug" => Some(8),
+        "september" | "sep" => Some(9),
+        "october" | "oct" => Some(10),
+        "november" | "nov" => Some(11),
+        "december" | "dec" => Some(12),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
     pub struct BaseHolyday {
     pub name: String,
     pub date: Option<String>,
     pub relative: bool,
-    pub condition: Option<Condition>,
+    pub condition: Option<RelativeCondition>,
+    // The article establishing the holiday, e.g. `国民の祝日に関する法律 第2条`
+    pub law_reference: Option<String>,
+    // First and last years the rule is observed, open-ended when `None`
+    pub since: Option<u32>,
+    pub until: Option<u32>,
+}
+
+impl BaseHolyday {
+    // Whether the rule is in force in `year`, e.g. 天皇誕生日 on 12/23 only from 1989 to 2018
+    pub fn in_force(&self, year: u32) -> bool {
+        self.since.is_none_or(|since| since <= year) && self.until.is_none_or(|until| year <= until)
+    }
 }
 
-#[derive(Debug)]
-pub struct EquinoxDay {
+// A holiday moved to another date in one year only, e.g. for the Tokyo Olympics
+#[derive(Debug, Clone)]
+pub struct SpecialHoliday {
+    pub year: u32,
     pub name: String,
+    // `M/D`, like the dates in `base.csv`
     pub date: String,
 }
 
-#[derive(Debug)]
-pub struct Equinox {
+// A holiday held in one year only by its own law, e.g. 即位の日 in 2019
+#[derive(Debug, Clone)]
+pub struct OneOffHoliday {
     pub year: u32,
-    pub equinox: Vec<EquinoxDay>,
+    pub name: String,
+    // `M/D`, like the dates in `base.csv`
+    pub date: String,
+    pub law_reference: String,
 }
+
 // List of Japanese Holidays throughout the Year
-#[allow(dead_code)]
 pub fn get_schedule()-> Result<Vec<BaseHolyday>> {
-    //let path = format!("{}/src/utils/base.csv", env!("CARGO_MANIFEST_DIR"));
+    Ok(schedule()?.to_vec())
+}
+
+// The embedded base data, parsed once on first use and shared afterwards.
+pub fn schedule()-> Result<&'static [BaseHolyday]> {
+    static SCHEDULE: OnceLock<Result<Vec<BaseHolyday>, String>> = OnceLock::new();
+    match SCHEDULE.get_or_init(|| parse_schedule(BASE_DATA).map_err(|e| e.to_string())) {
+        Ok(schedule) => Ok(schedule),
+        Err(err) => Err(anyhow!("{}", err)),
+    }
+}
+
+// Parse holiday rules laid out like `base.csv` (name,date,relative,condition,law_reference,since,until).
+// The columns after condition are optional.
+// Used with the embedded data, or with replacement data supplied at runtime.
+pub fn parse_schedule(data: &[u8])-> Result<Vec<BaseHolyday>> {
     let mut base_dates: Vec<BaseHolyday> = Vec::new();
-    let mut reader = csv::Reader::from_reader(BASE_DATA);
+    let mut reader = csv::Reader::from_reader(data);
     for result in reader.records() {
         match result {
             Ok(record) => {
                 let m: Vec<String> = record.iter().map(|x| x.to_string()).collect();
+                if m.len() < 4 {
+                    return Err(anyhow!("base holiday record {:?} must have name, date, relative and condition columns", m));
+                }
                 let value = BaseHolyday {
                     name: m[0].to_string(),
                     date: if m[1].is_empty() { None } else { Some(m[1].to_string())},
-                    relative: match m[2].parse() {
-                        Ok(v) => v,
-                        Err(_) => false,
-                    },
+                    relative: m[2].parse().unwrap_or_default(),
                     condition: if m[3].is_empty() { None } else {
-                        let c: Vec<String> = m[3].split(":").map(|x| x.to_string()).collect();
-                        Some(Condition {
-                            month: c[0].to_string(),
-                            n: match c[1].parse() {
-                                Ok(v) => v,
-                                Err(_) => 0,
-                            },
-                            weekday: c[2].to_string(),
-                        })
+                        Some(m[3].parse().map_err(|e| anyhow!("{}: {}", m[0], e))?)
                     },
+                    law_reference: m.get(4).filter(|x| !x.is_empty()).cloned(),
+                    since: parse_year_column(&m, 5)?,
+                    until: parse_year_column(&m, 6)?,
                 };
                 base_dates.push(value);
             },
@@ -93,38 +202,375 @@ pub fn get_schedule()-> Result<Vec<BaseHolyday>> {
     Ok(base_dates)
 }
 
-//　Basic data on Japanese national holidays, the vernal equinox and autumnal equinox, will be returned.
-#[allow(dead_code)]
-pub fn get_equinox_dates()->Result<Vec<Equinox>> {
-    let mut equinox_dates: Vec<Vec<String>> = Vec::new();
-    let mut reader = csv::Reader::from_reader(BASE_EQUINOX);
-    let mut records: Vec<Equinox> = Vec::new();
+// An optional year column of `base.csv`, empty when open-ended.
+fn parse_year_column(m: &[String], index: usize)-> Result<Option<u32>> {
+    match m.get(index).map(|x| x.trim()).filter(|x| !x.is_empty()) {
+        Some(year) => Ok(Some(year.parse().map_err(|_| anyhow!("invalid year {:?} for {}", year, m[0]))?)),
+        None => Ok(None),
+    }
+}
+
+// Parse year-specific relocations laid out like `special_holidays.csv` (year,name,date).
+pub fn parse_special_holidays(data: &[u8])-> Result<Vec<SpecialHoliday>> {
+    let mut reader = csv::Reader::from_reader(data);
+    let mut records: Vec<SpecialHoliday> = Vec::new();
     for result in reader.records() {
-        match result {
-            Ok(record) => {
-                let m: Vec<String> = record.iter().map(|x| x.to_string()).collect();
-                equinox_dates.push(m);
-            },
-            Err(err) => println!("{:?}", err),
+        let record = result?;
+        let m: Vec<String> = record.iter().map(|x| x.trim().to_string()).collect();
+        if m.len() < 3 {
+            return Err(anyhow!("special holiday record {:?} must have year, name and date columns", m));
         }
+        let year = m[0].parse().map_err(|_| anyhow!("invalid year {:?} for {}", m[0], m[1]))?;
+        records.push(SpecialHoliday { year, name: m[1].clone(), date: m[2].clone() });
     }
-    for date in equinox_dates {
-        let year = date[0].parse::<u32>().unwrap();
-        let day = Equinox {
-            year: year,
-            equinox: vec![
-                EquinoxDay {
-                    name: "春分の日".to_string(),
-                    date: date[1].to_string(),
-                },
-                EquinoxDay {
-                    name: "秋分の日".to_string(),
-                    date: date[2].to_string(),
-                },
-            ],
-        };
-        records.push(day);
+    Ok(records)
+}
+
+// Parse one-off holidays laid out like `one_off_holidays.csv` (year,name,date,law_reference).
+pub fn parse_one_off_holidays(data: &[u8])-> Result<Vec<OneOffHoliday>> {
+    let mut reader = csv::Reader::from_reader(data);
+    let mut records: Vec<OneOffHoliday> = Vec::new();
+    for result in reader.records() {
+        let record = result?;
+        let m: Vec<String> = record.iter().map(|x| x.trim().to_string()).collect();
+        if m.len() < 4 {
+            return Err(anyhow!("one-off holiday record {:?} must have year, name, date and law_reference columns", m));
+        }
+        let year = m[0].parse().map_err(|_| anyhow!("invalid year {:?} for {}", m[0], m[1]))?;
+        records.push(OneOffHoliday { year, name: m[1].clone(), date: m[2].clone(), law_reference: m[3].clone() });
     }
     Ok(records)
 }
 
+// A holiday of a user's own list, e.g. a company calendar
+#[derive(Debug, Clone)]
+pub struct CustomHoliday {
+    // `YYYY-MM-DD`
+    pub date: String,
+    pub name: String,
+}
+
+// Parse a user's holiday list laid out as `date,name`, with a header row.
+pub fn parse_custom_holidays(data: &[u8])-> Result<Vec<CustomHoliday>> {
+    let mut reader = csv::Reader::from_reader(data);
+    let mut records: Vec<CustomHoliday> = Vec::new();
+    for result in reader.records() {
+        let record = result?;
+        let m: Vec<String> = record.iter().map(|x| x.trim().to_string()).collect();
+        if m.len() < 2 || m[1].is_empty() {
+            return Err(anyhow!("custom holiday record {:?} must have date and name columns", m));
+        }
+        records.push(CustomHoliday { date: m[0].clone(), name: m[1].clone() });
+    }
+    Ok(records)
+}
+
+// The embedded relocations, parsed once on first use and shared afterwards.
+pub fn special_schedule()-> Result<&'static [SpecialHoliday]> {
+    static SPECIAL: OnceLock<Result<Vec<SpecialHoliday>, String>> = OnceLock::new();
+    match SPECIAL.get_or_init(|| parse_special_holidays(SPECIAL_DATA).map_err(|e| e.to_string())) {
+        Ok(special) => Ok(special),
+        Err(err) => Err(anyhow!("{}", err)),
+    }
+}
+
+// The embedded one-off holidays, parsed once on first use and shared afterwards.
+pub fn one_off_schedule()-> Result<&'static [OneOffHoliday]> {
+    static ONE_OFF: OnceLock<Result<Vec<OneOffHoliday>, String>> = OnceLock::new();
+    match ONE_OFF.get_or_init(|| parse_one_off_holidays(ONE_OFF_DATA).map_err(|e| e.to_string())) {
+        Ok(one_off) => Ok(one_off),
+        Err(err) => Err(anyhow!("{}", err)),
+    }
+}
+
+// A stable hash of the embedded data, base.csv, special_holidays.csv, one_off_holidays.csv and the equinox table, as 16 hex digits.
+// It changes whenever the data does, so clients can invalidate caches across crate versions.
+pub fn data_hash() -> &'static str {
+    static HASH: OnceLock<String> = OnceLock::new();
+    HASH.get_or_init(|| dataset_hash(BASE_DATA, SPECIAL_DATA, ONE_OFF_DATA, EQUINOX_TABLE))
+}
+
+// FNV-1a, which unlike `DefaultHasher` gives the same value on every platform and Rust release,
+// for hashes that are published, such as `data_hash` and the iCalendar UIDs.
+pub(crate) struct StableHasher(u64);
+
+impl StableHasher {
+    pub(crate) fn new() -> Self {
+        StableHasher(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= u64::from(*b);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    // Writes the length first, which keeps the boundaries between consecutive parts
+    pub(crate) fn write_part(&mut self, bytes: &[u8]) {
+        self.write(&(bytes.len() as u64).to_le_bytes());
+        self.write(bytes);
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+// `data_hash` of the given data, e.g. replacement data supplied at runtime.
+pub fn dataset_hash(base: &[u8], special: &[u8], one_off: &[u8], equinoxes: &[(u32, u32, u32)]) -> String {
+    let mut hasher = StableHasher::new();
+    for part in [base, special, one_off] {
+        hasher.write_part(part);
+    }
+    for (year, spring, fall) in equinoxes {
+        hasher.write(&year.to_le_bytes());
+        hasher.write(&spring.to_le_bytes());
+        hasher.write(&fall.to_le_bytes());
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+// Names in the embedded base data, borrowed from the parsed `schedule`.
+// Empty when the base data fails to parse.
+pub fn schedule_names() -> Vec<&'static str> {
+    schedule()
+        .map(|schedule| schedule.iter().map(|d| d.name.as_str()).collect())
+        .unwrap_or_default()
+}
+
+// Days of March and September of the equinoxes in `year`, if within the compiled table.
+// Takes a `u32`, an `i32` or a `Year`, like the functions of `calendar`.
+pub fn equinox_days(year: impl Into<Year>) -> Option<(u32, u32)> {
+    // The table covers `EQUINOX_RANGE`, earlier than the holidays themselves
+    let year = u32::try_from(year.into()).ok()?;
+    EQUINOX_TABLE
+        .binary_search_by_key(&year, |x| x.0)
+        .ok()
+        .map(|i| (EQUINOX_TABLE[i].1, EQUINOX_TABLE[i].2))
+}
+
+// Where the equinox days come from, see `EquinoxSource::days`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EquinoxSource {
+    // Only the officially announced days, up to `EQUINOX_OFFICIAL_UNTIL`; none for later years
+    OfficialTable,
+    // The approximation formula alone, for every year of the compiled range
+    Formula,
+    // The compiled table: the formula with the projected dates of `equinox_base_dates.csv` applied
+    #[default]
+    TableThenFormula,
+}
+
+impl EquinoxSource {
+    // Days of March and September of the equinoxes in `year`, or `None` if this source has none.
+    pub fn days(self, year: impl Into<Year>) -> Option<(u32, u32)> {
+        let year = u32::try_from(year.into()).ok()?;
+        match self {
+            EquinoxSource::OfficialTable if year > EQUINOX_OFFICIAL_UNTIL => None,
+            EquinoxSource::OfficialTable | EquinoxSource::TableThenFormula => equinox_days(year),
+            EquinoxSource::Formula => EQUINOX_FORMULA
+                .binary_search_by_key(&year, |x| x.0)
+                .ok()
+                .map(|i| (EQUINOX_FORMULA[i].1, EQUINOX_FORMULA[i].2)),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use std::collections::HashSet;
+    use chrono::NaiveDate;
+
+    #[test]
+    pub fn test_schedule_names_are_unique() {
+        // A name recurs only for rules of different periods, e.g. 天皇誕生日
+        let schedule = super::get_schedule().unwrap();
+        for year in 1948..=2100 {
+            let mut names = HashSet::new();
+            for d in schedule.iter().filter(|d| d.in_force(year)) {
+                assert!(names.insert(d.name.as_str()), "duplicate holiday name {} in {}", d.name, year);
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_schedule_periods() {
+        let schedule = super::get_schedule().unwrap();
+        let emperor: Vec<(Option<String>, Option<u32>, Option<u32>)> = schedule.iter()
+            .filter(|d| d.name == "天皇誕生日")
+            .map(|d| (d.date.clone(), d.since, d.until))
+            .collect();
+        assert_eq!(emperor, vec![
+            (Some("4/29".to_string()), Some(1949), Some(1988)),
+            (Some("12/23".to_string()), Some(1989), Some(2018)),
+            (Some("2/23".to_string()), Some(2020), None),
+        ]);
+        assert!(schedule.iter().all(|d| d.since.is_some() && d.since <= d.until.or(d.since)));
+        let data = "name,date,relative,condition,law_reference,since\n元旦,1/1,false,,,soon\n";
+        assert!(super::parse_schedule(data.as_bytes()).is_err());
+        // The period columns are optional
+        let data = "name,date,relative,condition\n元旦,1/1,false,\n";
+        assert!(super::parse_schedule(data.as_bytes()).unwrap()[0].in_force(1900));
+        // A row without the condition column is an error, not a panic
+        let data = "name,date,relative\n元旦,1/1,false\n";
+        assert!(super::parse_schedule(data.as_bytes()).is_err());
+    }
+
+    #[test]
+    pub fn test_equinox_formula_matches_table() {
+        let table: Vec<(u32, String, String)> = include_str!("../resources/equinox_base_dates.csv")
+            .lines()
+            .skip(1)
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let m: Vec<&str> = line.split(',').map(str::trim).collect();
+                (m[0].parse().unwrap(), m[1].to_string(), m[2].to_string())
+            })
+            .collect();
+        assert_eq!(table.len(), 31);
+        for (year, spring, fall) in table {
+            // a custom build range may not cover every table year
+            let formula = match super::EQUINOX_FORMULA.iter().find(|x| x.0 == year) {
+                Some(v) => v,
+                None => continue,
+            };
+            assert_eq!(format!("3/{}", formula.1), spring, "spring {}", year);
+            assert_eq!(format!("9/{}", formula.2), fall, "fall {}", year);
+            assert_eq!(super::equinox_days(year), Some((formula.1, formula.2)));
+        }
+    }
+
+    #[test]
+    pub fn test_equinox_table_range() {
+        let (start, end) = super::EQUINOX_RANGE;
+        assert!((start..=end).all(|year| super::equinox_days(year).is_some()));
+        assert!(super::EQUINOX_TABLE.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(super::equinox_days(super::EQUINOX_TABLE.last().unwrap().0 + 1), None);
+    }
+
+    #[test]
+    pub fn test_schedule_names_match_schedule() {
+        let names: Vec<String> = super::get_schedule().unwrap().into_iter().map(|d| d.name).collect();
+        assert_eq!(super::schedule_names(), names);
+
+        // Quoted names keep their commas, as the names come from the csv reader
+        let schedule = super::parse_schedule("name,date,relative,condition\n\"元旦,元日\",1/1,false,\n".as_bytes()).unwrap();
+        assert_eq!(schedule[0].name, "元旦,元日");
+        assert_eq!(schedule[0].date.as_deref(), Some("1/1"));
+    }
+
+    #[test]
+    pub fn test_schedule_fixed_dates_are_valid() {
+        // A leap year accepts every real month/day; a common year then rejects Feb 29 entries
+        for d in super::get_schedule().unwrap() {
+            if d.relative {
+                assert!(d.condition.is_some(), "{} is relative but has no condition", d.name);
+                continue;
+            }
+            let date = d.date.unwrap_or_else(|| panic!("{} has no date", d.name));
+            let parts: Vec<u32> = date.split('/').map(|x| x.parse().unwrap()).collect();
+            assert_eq!(parts.len(), 2, "{} has malformed date {}", d.name, date);
+            assert!(NaiveDate::from_ymd_opt(2024, parts[0], parts[1]).is_some(), "{} has invalid date {}", d.name, date);
+            assert!(NaiveDate::from_ymd_opt(2023, parts[0], parts[1]).is_some(), "{} only exists in leap years", d.name);
+        }
+    }
+
+    #[test]
+    pub fn test_special_holidays_name_scheduled_holidays() {
+        let names = super::schedule_names();
+        for d in super::special_schedule().unwrap() {
+            assert!(names.contains(&d.name.as_str()), "{} in {} is not in the base data", d.name, d.year);
+        }
+        assert!(super::parse_special_holidays("year,name,date\nnext,海の日,7/22\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    pub fn test_one_off_holidays() {
+        let one_off = super::one_off_schedule().unwrap();
+        let names: Vec<(u32, &str, &str)> = one_off.iter().map(|d| (d.year, d.name.as_str(), d.date.as_str())).collect();
+        assert_eq!(names, vec![
+            (1959, "皇太子明仁親王の結婚の儀", "4/10"),
+            (1989, "昭和天皇の大喪の礼", "2/24"),
+            (1990, "即位礼正殿の儀", "11/12"),
+            (1993, "皇太子徳仁親王の結婚の儀", "6/9"),
+            (2019, "即位の日", "5/1"),
+            (2019, "即位礼正殿の儀", "10/22"),
+        ]);
+        assert!(one_off.iter().all(|d| !d.law_reference.is_empty()));
+        assert!(super::parse_one_off_holidays("year,name,date,law_reference\n2019,即位の日,5/1\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    pub fn test_data_hash() {
+        let hash = super::data_hash();
+        assert_eq!(hash.len(), 16);
+        assert_eq!(super::data_hash(), hash);
+        assert_eq!(super::dataset_hash(super::BASE_DATA, super::SPECIAL_DATA, super::ONE_OFF_DATA, super::EQUINOX_TABLE), hash);
+        // Replacement data changes it
+        let base = String::from_utf8(super::BASE_DATA.to_vec()).unwrap().replace("山の日,8/11", "山の日,8/12");
+        assert_ne!(super::dataset_hash(base.as_bytes(), super::SPECIAL_DATA, super::ONE_OFF_DATA, super::EQUINOX_TABLE), hash);
+        assert_ne!(super::dataset_hash(super::BASE_DATA, super::SPECIAL_DATA, super::ONE_OFF_DATA, &super::EQUINOX_TABLE[1..]), hash);
+        // Moving a byte from one file to the other does too
+        assert_ne!(super::dataset_hash(b"ab", b"c", b"", &[]), super::dataset_hash(b"a", b"bc", b"", &[]));
+    }
+
+    #[test]
+    pub fn test_relative_condition() {
+        use chrono::Weekday;
+        use super::RelativeCondition;
+        use super::super::error::HolidayError;
+        assert_eq!("january:2:monday".parse::<RelativeCondition>().unwrap(), RelativeCondition { month: 1, nth: 2, weekday: Weekday::Mon });
+        assert_eq!(" Sep : 3 : Mon ".parse::<RelativeCondition>().unwrap(), RelativeCondition { month: 9, nth: 3, weekday: Weekday::Mon });
+        for (input, reason) in [
+            ("january:2", "expected month:n:weekday, but got 2 components"),
+            ("jly:3:monday", "unknown month \"jly\""),
+            ("july:0:monday", "n must be 1-5, but got \"0\""),
+            ("july:6:monday", "n must be 1-5, but got \"6\""),
+            ("july:third:monday", "n must be 1-5, but got \"third\""),
+            ("july:3:mondey", "unknown weekday \"mondey\""),
+        ] {
+            match input.parse::<RelativeCondition>() {
+                Err(HolidayError::InvalidCondition { condition, reason: r }) => {
+                    assert_eq!((condition.as_str(), r.as_str()), (input, reason));
+                }
+                other => panic!("{:?} parsed as {:?}", input, other),
+            }
+        }
+        // Every condition of the embedded data parses
+        assert!(super::schedule().unwrap().iter().filter(|d| d.relative).all(|d| d.condition.is_some()));
+    }
+
+    #[test]
+    pub fn test_equinox_source() {
+        use super::EquinoxSource;
+        // Inside the official table all three agree
+        for source in [EquinoxSource::OfficialTable, EquinoxSource::Formula, EquinoxSource::TableThenFormula] {
+            assert_eq!(source.days(2024), Some((20, 22)));
+        }
+        // Past it only the formula and the lenient default have days
+        let year = super::EQUINOX_OFFICIAL_UNTIL + 3;
+        assert_eq!(EquinoxSource::OfficialTable.days(year), None);
+        assert_eq!(EquinoxSource::Formula.days(year), super::equinox_days(year));
+        assert!(EquinoxSource::TableThenFormula.days(year).is_some());
+        assert_eq!(EquinoxSource::default(), EquinoxSource::TableThenFormula);
+        assert_eq!(EquinoxSource::Formula.days(super::EQUINOX_RANGE.1 + 1), None);
+    }
+
+    #[test]
+    pub fn test_equinox_days_year_types() {
+        use super::super::year::Year;
+        let expected = Some((20, 22));
+        assert_eq!(super::equinox_days(2024u32), expected);
+        assert_eq!(super::equinox_days(2024i32), expected);
+        assert_eq!(super::equinox_days(Year::new(2024).unwrap()), expected);
+        assert_eq!(super::equinox_days(-2024), None);
+        // The calendar places the same days whichever type the year has
+        let equinoxes = |days: Vec<super::super::calendar::Holiday>| -> Vec<NaiveDate> {
+            days.into_iter().filter(|h| h.category == super::super::calendar::HolidayCategory::Equinox).map(|h| h.date).collect()
+        };
+        let by_u32 = equinoxes(super::super::calendar::holidays(2024u32).unwrap());
+        assert_eq!(by_u32, equinoxes(super::super::calendar::holidays(2024i32).unwrap()));
+        assert_eq!(by_u32, vec![NaiveDate::from_ymd_opt(2024, 3, 20).unwrap(), NaiveDate::from_ymd_opt(2024, 9, 22).unwrap()]);
+    }
+}
@@ -0,0 +1,129 @@
+//! # Year
+//! A calendar year the holidays can be computed for.
+//!
+//! `Year::new` accepts 1948, when the Act on National Holidays (国民の祝日に関する法律) came into force,
+//! through the last year of the compiled equinox table (`EQUINOX_RANGE`, 2099 unless the build changes it).
+//! Outside that span there are no statutory holidays, or the equinox days are unknown.
+//!
+//! Every function of `calendar`, `ical` and `timebase` taking a year takes `impl Into<Year>`, so a raw `i32`
+//! or `u32` works as well. Those conversions are not validated themselves; the functions check the range and
+//! fail with `HolidayError::YearOutOfRange` for a year `Year::new` would reject.
+//!
+//! ```
+//! use jpn_holiday_atlas::datebook::calendar::holidays;
+//! use jpn_holiday_atlas::datebook::year::Year;
+//!
+//! let year = Year::new(2024).unwrap();
+//! assert_eq!(holidays(year).unwrap().len(), 21);
+//! assert!(Year::new(1900).is_err());
+//! ```
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use super::error::HolidayError;
+use super::timebase::EQUINOX_RANGE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Year(i32);
+
+impl Year {
+    // The first and last years accepted by `Year::new`
+    pub const MIN: i32 = 1948;
+    pub const MAX: i32 = EQUINOX_RANGE.1 as i32;
+    // `Year::MIN..=Year::MAX` as the calendar computation takes it
+    pub(crate) const SUPPORTED: RangeInclusive<u32> = Year::MIN as u32..=Year::MAX as u32;
+
+    /// `year`, if within `Year::MIN..=Year::MAX`.
+    pub fn new(year: i32) -> Result<Year, HolidayError> {
+        if (Year::MIN..=Year::MAX).contains(&year) {
+            Ok(Year(year))
+        } else {
+            Err(HolidayError::YearOutOfRange(year.into()))
+        }
+    }
+
+    pub fn get(self) -> i32 {
+        self.0
+    }
+
+    // The year as the calendar computation takes it, if within `Year::MIN..=Year::MAX`
+    pub(crate) fn to_u32(self) -> Result<u32, HolidayError> {
+        u32::try_from(Year::new(self.0)?)
+    }
+}
+
+impl From<Year> for i32 {
+    fn from(year: Year) -> Self {
+        year.0
+    }
+}
+
+// Fails for unvalidated negative years only.
+impl TryFrom<Year> for u32 {
+    type Error = HolidayError;
+
+    fn try_from(year: Year) -> Result<Self, Self::Error> {
+        u32::try_from(year.0).map_err(|_| HolidayError::YearOutOfRange(year.0.into()))
+    }
+}
+
+// Unvalidated, see the module docs.
+impl From<i32> for Year {
+    fn from(year: i32) -> Self {
+        Year(year)
+    }
+}
+
+// Unvalidated; years beyond `i32::MAX` saturate, which keeps them out of range.
+impl From<u32> for Year {
+    fn from(year: u32) -> Self {
+        Year(i32::try_from(year).unwrap_or(i32::MAX))
+    }
+}
+
+impl fmt::Display for Year {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use pretty_assertions::assert_eq;
+    use super::super::error::HolidayError;
+    use super::Year;
+
+    #[test]
+    pub fn test_new() {
+        assert_eq!(Year::new(1948).unwrap().get(), 1948);
+        assert_eq!(Year::new(Year::MAX).unwrap().get(), Year::MAX);
+        assert!(matches!(Year::new(1947), Err(HolidayError::YearOutOfRange(1947))));
+        assert!(matches!(Year::new(Year::MAX + 1), Err(HolidayError::YearOutOfRange(_))));
+        assert!(matches!(Year::new(-1), Err(HolidayError::YearOutOfRange(-1))));
+    }
+
+    #[test]
+    pub fn test_conversions() {
+        assert_eq!(Year::from(2024), Year::new(2024).unwrap());
+        assert_eq!(Year::from(2024u32), Year::new(2024).unwrap());
+        assert_eq!(Year::from(u32::MAX).get(), i32::MAX);
+        assert_eq!(Year::from(2024).to_u32().unwrap(), 2024);
+        assert!(matches!(Year::from(1900).to_u32(), Err(HolidayError::YearOutOfRange(1900))));
+        assert!(matches!(Year::from(Year::MAX + 1).to_u32(), Err(HolidayError::YearOutOfRange(_))));
+        assert!(matches!(Year::from(-5).to_u32(), Err(HolidayError::YearOutOfRange(-5))));
+        assert_eq!(Year::new(2024).unwrap().to_string(), "2024");
+        assert_eq!(i32::from(Year::new(2024).unwrap()), 2024);
+        assert_eq!(u32::try_from(Year::new(2024).unwrap()).unwrap(), 2024);
+        assert!(matches!(u32::try_from(Year::from(-5)), Err(HolidayError::YearOutOfRange(-5))));
+    }
+
+    #[test]
+    pub fn test_accepted_by_holidays() {
+        let year = Year::new(2024).unwrap();
+        assert_eq!(super::super::calendar::holidays(year).unwrap(), super::super::calendar::holidays(2024).unwrap());
+        assert!(super::super::calendar::holidays(-1).is_err());
+        let err = super::super::calendar::holidays(2100).unwrap_err();
+        assert!(matches!(err.downcast_ref::<HolidayError>(), Some(HolidayError::YearOutOfRange(2100))));
+        assert!(super::super::calendar::holidays(3000).is_err());
+    }
+}
@@ -0,0 +1,57 @@
+//! # FFI
+//! C interface for embedding the crate in other runtimes, enabled with the `ffi` feature.
+//!
+//! Build a shared library with:
+//! ```sh
+//! cargo rustc --release --lib --crate-type cdylib --features ffi
+//! ```
+//!
+//! ## Ownership
+//! `holidays_c` returns a NUL-terminated UTF-8 string allocated by this library.
+//! The caller owns it and must release it with `free_holidays_c`, exactly once,
+//! and never with the C runtime's `free`.
+//! A null pointer is returned when the holidays cannot be computed, e.g. for a negative year.
+use std::ffi::{c_char, CString};
+use std::ptr;
+use crate::datebook::calendar::{holiday, OutputFormat};
+
+/// The holidays of `year` as a JSON C string, or null on error. Release it with `free_holidays_c`.
+#[no_mangle]
+pub extern "C" fn holidays_c(year: i32) -> *mut c_char {
+    let json = u32::try_from(year).ok().and_then(|year| holiday(OutputFormat::JSON, year).ok());
+    match json.and_then(|json| CString::new(json).ok()) {
+        Some(s) => s.into_raw(),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Releases a string returned by `holidays_c`. Null is ignored.
+///
+/// # Safety
+/// `ptr` must be null or a pointer returned by `holidays_c` that has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn free_holidays_c(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        drop(CString::from_raw(ptr));
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use pretty_assertions::assert_eq;
+    use std::ffi::CStr;
+    use super::{holidays_c, free_holidays_c};
+
+    #[test]
+    pub fn test_holidays_c() {
+        let ptr = holidays_c(2024);
+        assert!(!ptr.is_null());
+        let json = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string();
+        unsafe { free_holidays_c(ptr) };
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value.as_array().unwrap().len(), 21);
+
+        assert!(holidays_c(-1).is_null());
+        unsafe { free_holidays_c(std::ptr::null_mut()) };
+    }
+}
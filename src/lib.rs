@@ -0,0 +1,10 @@
+//! # jpn_holiday_atlas
+//! Japanese national holidays, see [`datebook`], or [`prelude`] for the common items in one import.
+pub mod datebook;
+pub mod prelude;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "tokio")]
+pub mod asynchronous;
+#[cfg(feature = "chrono-tz")]
+pub use chrono_tz;
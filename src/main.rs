@@ -1,9 +1,8 @@
 // Title: main.rs
 // Compare this snippet from src/datebook/mod.rs:
 
-mod datebook;
-use datebook::calendar::OutputFormat;
-use datebook::calendar::holiday;
+use jpn_holiday_atlas::datebook::calendar::OutputFormat;
+use jpn_holiday_atlas::datebook::calendar::holiday;
 fn main() {
     let year = 2024;
     let format = OutputFormat::JSON;
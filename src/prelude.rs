@@ -0,0 +1,27 @@
+//! # Prelude
+//! The commonly used functions and types in one import.
+//!
+//! ```
+//! use jpn_holiday_atlas::prelude::*;
+//! use chrono::NaiveDate;
+//!
+//! assert!(is_holiday(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()).unwrap());
+//! let days: Vec<Holiday> = holidays(Year::new(2024).unwrap()).unwrap();
+//! assert_eq!(days.len(), 21);
+//! let json = OutputBuilder::new(OutputFormat::JSON).fields(Fields::ALL).build(2024).unwrap();
+//! assert!(json.starts_with('['));
+//! ```
+//!
+//! | Item | Description |
+//! | --- | --- |
+//! | `holidays`, `holiday` | The holidays of a year, as `Holiday` values or formatted output |
+//! | `is_holiday`, `holiday_status` | Whether a date is a holiday, and its name |
+//! | `Holiday`, `HolidayCategory` | A computed holiday and where its date comes from |
+//! | `OutputBuilder`, `OutputFormat`, `Fields` | Formatted output with optional fields |
+//! | `Calendar`, `JapanNationalCalendar`, `CombinedCalendar` | Holiday sources |
+//! | `Year`, `HolidayError` | A validated year and the errors of the lookups |
+pub use crate::datebook::calendar::{holiday, holiday_status, holidays, is_holiday};
+pub use crate::datebook::calendar::{Calendar, CombinedCalendar, JapanNationalCalendar};
+pub use crate::datebook::calendar::{Fields, Holiday, HolidayCategory, OutputBuilder, OutputFormat};
+pub use crate::datebook::error::HolidayError;
+pub use crate::datebook::year::Year;
@@ -0,0 +1,97 @@
+// Checks of the data parsing in build.rs, which cannot have tests of its own. The script is
+// included into a module so that the checks can reach its private functions.
+#[allow(dead_code)]
+mod build {
+    include!("../build.rs");
+
+    #[test]
+    fn equinox_override_is_parsed() {
+        assert_eq!(parse_override("2024,3/20,9/22"), Ok((2024, 20, 22)));
+    }
+
+    #[test]
+    fn equinox_override_malformed_date() {
+        let err = parse_override("2025,3/32,9/23").unwrap_err();
+        assert!(err.contains("3/32"), "{}", err);
+        assert!(err.contains("2025"), "{}", err);
+        let err = parse_override("2025,9/23,3/20").unwrap_err();
+        assert!(err.contains("9/23"), "{}", err);
+    }
+
+    #[test]
+    fn equinox_override_malformed_year() {
+        let err = parse_override("20x5,3/20,9/23").unwrap_err();
+        assert!(err.contains("20x5"), "{}", err);
+        assert!(parse_override("2024,3/20").is_err());
+    }
+
+    // `base.csv` with the given rows
+    fn base(rows: &[&str]) -> String {
+        format!("{}\n{}\n", BASE_HEADER, rows.join("\n"))
+    }
+
+    #[test]
+    fn embedded_data_validates() {
+        let rules = validate_base(&read(BASE)).unwrap();
+        assert_eq!(validate_special(&read(SPECIAL), &rules), Ok(()));
+        assert_eq!(validate_one_off(&read(ONE_OFF), &rules), Ok(()));
+    }
+
+    #[test]
+    fn base_rejects_a_name_in_force_twice() {
+        let text = base(&[
+            "天皇誕生日,4/29,false,,,1949,1988",
+            "天皇誕生日,12/23,false,,,1989,2018",
+        ]);
+        assert_eq!(validate_base(&text).unwrap().len(), 2);
+        let text = base(&[
+            "天皇誕生日,4/29,false,,,1949,1990",
+            "天皇誕生日,12/23,false,,,1989,2018",
+        ]);
+        let err = validate_base(&text).unwrap_err();
+        assert!(err.starts_with("3: 天皇誕生日 overlaps its rule in force 1949-1990"), "{}", err);
+        // Open-ended rules overlap every later one
+        let err = validate_base(&base(&["元旦,1/1,false,,,1948,", "元旦,1/1,false,,,2000,"])).unwrap_err();
+        assert!(err.contains("overlaps"), "{}", err);
+    }
+
+    #[test]
+    fn base_rejects_an_unknown_condition() {
+        let err = validate_base(&base(&["成人の日,,true,janury:2:monday,,2000,"])).unwrap_err();
+        assert!(err.contains("unknown month \"janury\""), "{}", err);
+        let err = validate_base(&base(&["成人の日,,true,january:2:mondey,,2000,"])).unwrap_err();
+        assert!(err.contains("unknown weekday \"mondey\""), "{}", err);
+        let err = validate_base(&base(&["成人の日,,true,january:6:monday,,2000,"])).unwrap_err();
+        assert!(err.contains("n must be 1-5"), "{}", err);
+        let err = validate_base(&base(&["成人の日,,true,january:monday,,2000,"])).unwrap_err();
+        assert!(err.contains("expected month:n:weekday"), "{}", err);
+        assert!(validate_base("name,date\n").is_err());
+    }
+
+    #[test]
+    fn special_rejects_a_holiday_not_in_force() {
+        let rules = validate_base(&base(&["海の日,7/20,false,,,1996,2002", "海の日,,true,july:3:monday,,2003,"])).unwrap();
+        assert_eq!(validate_special("year,name,date\n2021,海の日,7/22\n", &rules), Ok(()));
+        let err = validate_special("year,name,date\n1990,海の日,7/22\n", &rules).unwrap_err();
+        assert_eq!(err, "2: 海の日 is not a holiday of base.csv in 1990");
+        let err = validate_special("year,name,date\n2021,山の日,8/8\n", &rules).unwrap_err();
+        assert!(err.contains("山の日 is not a holiday"), "{}", err);
+        let err = validate_special("year,name,date\n2021,海の日,7/22\n2021,海の日,7/23\n", &rules).unwrap_err();
+        assert!(err.contains("relocated twice"), "{}", err);
+        let err = validate_special("year,name,date\n2021,海の日,2/29\n", &rules).unwrap_err();
+        assert!(err.contains("does not exist"), "{}", err);
+    }
+
+    #[test]
+    fn one_off_rejects_malformed_rows() {
+        let rules = validate_base(&base(&["元旦,1/1,false,,,1948,"])).unwrap();
+        let header = "year,name,date,law_reference";
+        assert_eq!(validate_one_off(&format!("{}\n2019,即位の日,5/1,法律\n", header), &rules), Ok(()));
+        let err = validate_one_off(&format!("{}\n2019,元旦,5/1,法律\n", header), &rules).unwrap_err();
+        assert!(err.contains("元旦 is a holiday of base.csv"), "{}", err);
+        let err = validate_one_off(&format!("{}\n2019,即位の日,5/1,\n", header), &rules).unwrap_err();
+        assert!(err.starts_with("2: expected"), "{}", err);
+        let err = validate_one_off(&format!("{}\n2019,即位の日,5/1,法律\n2019,即位の日,5/1,法律\n", header), &rules).unwrap_err();
+        assert!(err.contains("listed twice"), "{}", err);
+    }
+}
@@ -0,0 +1,58 @@
+// Property-based checks of the holiday computation over the years of the current holiday law.
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::ops::RangeInclusive;
+
+use jpn_holiday_atlas::datebook::calendar::{holidays, validate_year};
+use jpn_holiday_atlas::datebook::timebase::{AUTUMNAL_EQUINOX_NAME, AUTUMNAL_EQUINOX_SINCE, VERNAL_EQUINOX_NAME, VERNAL_EQUINOX_SINCE};
+use jpn_holiday_atlas::datebook::year::Year;
+use proptest::prelude::*;
+
+// The years with a holiday law and a known equinox
+const YEARS: RangeInclusive<u32> = Year::MIN as u32..=Year::MAX as u32;
+
+proptest! {
+    #[test]
+    fn years_pass_validation(year in YEARS) {
+        prop_assert_eq!(validate_year(year), Ok(()));
+    }
+
+    #[test]
+    fn holidays_are_strictly_sorted(year in YEARS) {
+        let days = holidays(year).unwrap();
+        prop_assert!(days.windows(2).all(|w| w[0].date < w[1].date), "{:?}", days);
+    }
+
+    #[test]
+    fn no_date_has_two_statutory_holidays(year in YEARS) {
+        let days = holidays(year).unwrap();
+        let statutory: Vec<NaiveDate> = days.iter().filter(|h| !h.substitute).map(|h| h.date).collect();
+        let mut unique = statutory.clone();
+        unique.dedup();
+        prop_assert_eq!(unique.len(), statutory.len());
+    }
+
+    #[test]
+    fn substitutes_are_not_on_sunday(year in YEARS) {
+        for h in holidays(year).unwrap().iter().filter(|h| h.substitute) {
+            prop_assert_ne!(h.date.weekday(), Weekday::Sun, "{:?}", h);
+        }
+    }
+
+    #[test]
+    fn holidays_stay_near_the_year(year in YEARS) {
+        // A substitute for a holiday at the end of December may spill over by a few days
+        let start = NaiveDate::from_ymd_opt(year as i32, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(year as i32, 12, 31).unwrap() + Duration::days(7);
+        for h in holidays(year).unwrap() {
+            prop_assert!(h.date >= start && h.date <= end, "{:?}", h);
+        }
+    }
+
+    #[test]
+    fn one_equinox_of_each(year in YEARS) {
+        let days = holidays(year).unwrap();
+        let count = |name: &str| days.iter().filter(|h| h.name == name).count();
+        prop_assert_eq!(count(VERNAL_EQUINOX_NAME), usize::from(year >= VERNAL_EQUINOX_SINCE));
+        prop_assert_eq!(count(AUTUMNAL_EQUINOX_NAME), usize::from(year >= AUTUMNAL_EQUINOX_SINCE));
+    }
+}
@@ -0,0 +1,15 @@
+// Guards against accidental slowdowns of batch computation; see benches/holidays.rs for the numbers.
+use jpn_holiday_atlas::datebook::calendar::holidays;
+use std::time::{Duration, Instant};
+
+// Far above the few milliseconds a release build takes, so debug builds on slow CI machines pass too
+const BATCH_BOUND: Duration = Duration::from_secs(5);
+
+#[test]
+fn multi_decade_batch_is_fast() {
+    let start = Instant::now();
+    let total: usize = (1950..=2099).map(|year| holidays(year).unwrap().len()).sum();
+    let elapsed = start.elapsed();
+    assert!(total > 0);
+    assert!(elapsed < BATCH_BOUND, "1950-2099 took {:?}", elapsed);
+}
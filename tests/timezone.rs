@@ -0,0 +1,20 @@
+// The holiday list must not depend on the timezone of the machine computing it.
+use std::process::Command;
+
+fn run_with_tz(tz: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_jpn_holiday_atlas"))
+        .env("TZ", tz)
+        .output()
+        .expect("failed to run jpn_holiday_atlas");
+    assert!(output.status.success(), "TZ={} failed: {}", tz, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_output_is_identical_across_timezones() {
+    let expected = run_with_tz("Asia/Tokyo");
+    assert!(expected.contains("成人の日"));
+    for tz in ["UTC", "Pacific/Kiritimati", "Etc/GMT+12", "America/Santiago", "America/Asuncion"] {
+        assert_eq!(run_with_tz(tz), expected, "output differs for TZ={}", tz);
+    }
+}